@@ -0,0 +1,162 @@
+use std::{collections::{BTreeMap, BTreeSet}, fmt, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+
+use crate::bus::{self, Bus, Message};
+
+/// The extension of bag files in the project
+pub const EXTENSION: &str = "bag";
+/// The node that replayed messages are published from
+pub const PLAYER_NODE: &str = "/bag_player";
+
+const FORMAT: &str = "ide-bag";
+// increment this when the layout of the records changes
+const FORMAT_VERSION: u32 = 1;
+
+/// A bag is stored as JSON with one line per record, preceded by this header
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    format: String,
+    version: u32,
+}
+
+/// A message that was published at `time` seconds of simulated time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub time: f64,
+    pub topic: String,
+    #[serde(flatten)]
+    pub message: Message,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub fn write(records: &[Record]) -> Vec<u8> {
+    let header = Header {
+        format: FORMAT.to_owned(),
+        version: FORMAT_VERSION,
+    };
+    let mut data = serde_json::to_vec(&header).unwrap();
+    for record in records {
+        data.push(b'\n');
+        data.extend(serde_json::to_vec(record).unwrap());
+    }
+    data.push(b'\n');
+    data
+}
+
+/// Reads the records of a bag, which are sorted by time
+pub fn read(data: &[u8]) -> Result<Vec<Record>, Error> {
+    let text = std::str::from_utf8(data)
+        .map_err(|error| Error { line: 1, message: error.to_string() })?;
+    let mut lines = text.lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+        .filter(|(_, line)| !line.trim().is_empty());
+    let header = lines.next()
+        .ok_or_else(|| Error { line: 1, message: "the bag is empty".to_owned() })
+        .and_then(|(line, header)| serde_json::from_str::<Header>(header)
+            .map_err(|error| Error { line, message: error.to_string() }))?;
+    if header.format != FORMAT || header.version != FORMAT_VERSION {
+        return Err(Error {
+            line: 1,
+            message: format!("unsupported format {} version {}", header.format, header.version),
+        });
+    }
+    let mut records = lines
+        .map(|(line, record)| serde_json::from_str::<Record>(record)
+            .map_err(|error| Error { line, message: error.to_string() }))
+        .collect::<Result<Vec<_>, _>>()?;
+    records.sort_by(|a, b| a.time.total_cmp(&b.time));
+    Ok(records)
+}
+
+/// Collects the messages published on all topics except the `skipped` ones,
+/// e.g., the outputs of the simulated world, which would conflict with the
+/// world when the bag is replayed
+pub struct Recorder {
+    monitor: bus::Monitor,
+    skipped: BTreeSet<String>,
+    records: Vec<Record>,
+}
+
+impl Recorder {
+    pub fn new(bus: &Rc<Bus>, skipped: BTreeSet<String>) -> Recorder {
+        Recorder {
+            monitor: bus.create_monitor(),
+            skipped,
+            records: Vec::new(),
+        }
+    }
+
+    /// Records the messages that were published since the last call at `time`
+    pub fn record(&mut self, time: f64) {
+        self.records.extend(self.monitor.drain()
+            .into_iter()
+            .filter(|(_, topic, _)| !self.skipped.contains(topic))
+            .map(|(_, topic, message)| Record { time, topic, message }));
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    pub fn finish(self) -> Vec<Record> {
+        self.records
+    }
+}
+
+/// Publishes the records of a bag as the simulated time reaches them, except
+/// those on `skipped` topics, which the simulated world publishes itself
+pub struct Player {
+    records: Vec<Record>,
+    next: usize,
+    publishers: BTreeMap<String, bus::Publisher>,
+}
+
+impl Player {
+    pub fn new(mut records: Vec<Record>, skipped: &BTreeSet<String>, bus: &Rc<Bus>) -> Result<Player, bus::Error> {
+        records.retain(|record| !skipped.contains(&record.topic));
+        let mut publishers = BTreeMap::new();
+        for record in records.iter() {
+            if !publishers.contains_key(&record.topic) {
+                let publisher = bus.create_publisher(PLAYER_NODE, &record.topic, record.message.message_type())?;
+                publishers.insert(record.topic.clone(), publisher);
+            }
+        }
+        Ok(Player {
+            records,
+            next: 0,
+            publishers,
+        })
+    }
+
+    /// Publishes all records up to and including `time`
+    pub fn play(&mut self, time: f64) -> Result<(), bus::Error> {
+        while let Some(record) = self.records.get(self.next).filter(|record| record.time <= time) {
+            self.next += 1;
+            self.publishers[&record.topic].publish(record.message.clone())?;
+        }
+        Ok(())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next == self.records.len()
+    }
+}
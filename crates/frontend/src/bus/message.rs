@@ -0,0 +1,218 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// The types of messages that can be sent over the bus, named after their ROS interfaces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum MessageType {
+    Velocity,
+    Contact,
+    Range,
+    LaserScan,
+    Pose,
+    Odometry,
+}
+
+impl MessageType {
+    pub const ALL: [MessageType; 6] = [
+        MessageType::Velocity,
+        MessageType::Contact,
+        MessageType::Range,
+        MessageType::LaserScan,
+        MessageType::Pose,
+        MessageType::Odometry,
+    ];
+
+    /// The fully qualified name, e.g., `example_interfaces/msg/Velocity`
+    pub fn name(self) -> &'static str {
+        match self {
+            MessageType::Velocity => "example_interfaces/msg/Velocity",
+            MessageType::Contact => "example_interfaces/msg/Contact",
+            MessageType::Range => "sensor_msgs/msg/Range",
+            MessageType::LaserScan => "sensor_msgs/msg/LaserScan",
+            MessageType::Pose => "example_interfaces/msg/Pose",
+            MessageType::Odometry => "example_interfaces/msg/Odometry",
+        }
+    }
+
+    /// The fields that hold a single number, e.g., for plotting
+    pub fn numeric_fields(self) -> &'static [&'static str] {
+        match self {
+            MessageType::Velocity => &["left", "right"],
+            MessageType::Contact => &["x", "z"],
+            MessageType::Range => &["min_range", "max_range", "range"],
+            MessageType::LaserScan => &["angle_min", "angle_max", "angle_increment", "range_min", "range_max"],
+            MessageType::Pose => &["x", "z", "heading"],
+            MessageType::Odometry => &["x", "z", "heading", "linear", "angular"],
+        }
+    }
+
+    /// Looks up a type by its fully qualified name or just the name of the message
+    pub fn from_name(name: &str) -> Option<MessageType> {
+        MessageType::ALL.into_iter()
+            .find(|message_type| message_type.name() == name ||
+                message_type.name().rsplit('/').next() == Some(name))
+    }
+}
+
+impl fmt::Display for MessageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The angular velocities of the left and right wheels of a differential drive
+/// robot in rad/s, see [crate::simulation::Robot::wheel_velocities]
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Velocity {
+    pub left: f64,
+    pub right: f64,
+}
+
+/// A robot started touching the boundary of the world or another model at the
+/// point `x`, `z`, see [crate::simulation::CONTACTS_TOPIC]
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Contact {
+    pub model: String,
+    pub other: String,
+    pub x: f64,
+    pub z: f64,
+}
+
+/// A distance measured along a single ray, which is `max_range` if nothing
+/// was hit, all distances are in meters
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Range {
+    pub min_range: f64,
+    pub max_range: f64,
+    pub range: f64,
+}
+
+/// Distances measured along rays that start at `angle_min` and are
+/// `angle_increment` radians apart, angles are relative to the heading of the
+/// robot and increase counterclockwise. Rays that hit nothing measure `range_max`.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct LaserScan {
+    pub angle_min: f64,
+    pub angle_max: f64,
+    pub angle_increment: f64,
+    pub range_min: f64,
+    pub range_max: f64,
+    pub ranges: Vec<f64>,
+}
+
+/// A position on the ground plane in meters and a heading in radians, see
+/// [crate::simulation::Pose]
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Pose {
+    pub x: f64,
+    pub z: f64,
+    pub heading: f64,
+}
+
+/// The pose of a robot as estimated from its wheels, along with its linear
+/// velocity in m/s and its angular velocity in rad/s
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Odometry {
+    pub x: f64,
+    pub z: f64,
+    pub heading: f64,
+    pub linear: f64,
+    pub angular: f64,
+}
+
+/// A message, which is serialized as its type name and its fields, e.g.,
+/// `{"type": "example_interfaces/msg/Velocity", "data": {"left": 1.0, "right": -1.0}}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum Message {
+    #[serde(rename = "example_interfaces/msg/Velocity")]
+    Velocity(Velocity),
+    #[serde(rename = "example_interfaces/msg/Contact")]
+    Contact(Contact),
+    #[serde(rename = "sensor_msgs/msg/Range")]
+    Range(Range),
+    #[serde(rename = "sensor_msgs/msg/LaserScan")]
+    LaserScan(LaserScan),
+    #[serde(rename = "example_interfaces/msg/Pose")]
+    Pose(Pose),
+    #[serde(rename = "example_interfaces/msg/Odometry")]
+    Odometry(Odometry),
+}
+
+impl Message {
+    pub fn message_type(&self) -> MessageType {
+        match self {
+            Message::Velocity(_) => MessageType::Velocity,
+            Message::Contact(_) => MessageType::Contact,
+            Message::Range(_) => MessageType::Range,
+            Message::LaserScan(_) => MessageType::LaserScan,
+            Message::Pose(_) => MessageType::Pose,
+            Message::Odometry(_) => MessageType::Odometry,
+        }
+    }
+
+    /// Returns the value of a field listed in [MessageType::numeric_fields]
+    pub fn numeric_field(&self, field: &str) -> Option<f64> {
+        let value = match (self, field) {
+            (Message::Velocity(velocity), "left") => velocity.left,
+            (Message::Velocity(velocity), "right") => velocity.right,
+            (Message::Contact(contact), "x") => contact.x,
+            (Message::Contact(contact), "z") => contact.z,
+            (Message::Range(range), "min_range") => range.min_range,
+            (Message::Range(range), "max_range") => range.max_range,
+            (Message::Range(range), "range") => range.range,
+            (Message::LaserScan(scan), "angle_min") => scan.angle_min,
+            (Message::LaserScan(scan), "angle_max") => scan.angle_max,
+            (Message::LaserScan(scan), "angle_increment") => scan.angle_increment,
+            (Message::LaserScan(scan), "range_min") => scan.range_min,
+            (Message::LaserScan(scan), "range_max") => scan.range_max,
+            (Message::Pose(pose), "x") => pose.x,
+            (Message::Pose(pose), "z") => pose.z,
+            (Message::Pose(pose), "heading") => pose.heading,
+            (Message::Odometry(odometry), "x") => odometry.x,
+            (Message::Odometry(odometry), "z") => odometry.z,
+            (Message::Odometry(odometry), "heading") => odometry.heading,
+            (Message::Odometry(odometry), "linear") => odometry.linear,
+            (Message::Odometry(odometry), "angular") => odometry.angular,
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+impl From<Velocity> for Message {
+    fn from(velocity: Velocity) -> Message {
+        Message::Velocity(velocity)
+    }
+}
+
+impl From<Contact> for Message {
+    fn from(contact: Contact) -> Message {
+        Message::Contact(contact)
+    }
+}
+
+impl From<Range> for Message {
+    fn from(range: Range) -> Message {
+        Message::Range(range)
+    }
+}
+
+impl From<LaserScan> for Message {
+    fn from(scan: LaserScan) -> Message {
+        Message::LaserScan(scan)
+    }
+}
+
+impl From<Pose> for Message {
+    fn from(pose: Pose) -> Message {
+        Message::Pose(pose)
+    }
+}
+
+impl From<Odometry> for Message {
+    fn from(odometry: Odometry) -> Message {
+        Message::Odometry(odometry)
+    }
+}
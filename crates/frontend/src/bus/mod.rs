@@ -0,0 +1,455 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, VecDeque},
+    fmt,
+    pin::Pin,
+    rc::{Rc, Weak},
+    task::{Context, Poll, Waker}
+};
+
+use futures::Stream;
+
+pub mod message;
+pub mod parameter;
+
+pub use message::{Message, MessageType};
+pub use parameter::ParameterValue;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    InvalidTopicName(String),
+    TypeMismatch {
+        topic: String,
+        expected: MessageType,
+        found: MessageType,
+    },
+    InvalidParameterName(String),
+    ParameterAlreadyDeclared {
+        node: String,
+        name: String,
+    },
+    ParameterNotDeclared {
+        node: String,
+        name: String,
+    },
+    ParameterTypeMismatch {
+        name: String,
+        expected: &'static str,
+        found: &'static str,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidTopicName(name) => write!(f, "{name:?} is not a valid topic name"),
+            Error::TypeMismatch { topic, expected, found } =>
+                write!(f, "{topic} has the type {expected}, not {found}"),
+            Error::InvalidParameterName(name) => write!(f, "{name:?} is not a valid parameter name"),
+            Error::ParameterAlreadyDeclared { node, name } => write!(f, "{node} already declared the parameter {name}"),
+            Error::ParameterNotDeclared { node, name } => write!(f, "{node} has not declared the parameter {name}"),
+            Error::ParameterTypeMismatch { name, expected, found } =>
+                write!(f, "the parameter {name} has the type {expected}, not {found}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Resolves a topic name relative to the namespace of a node, e.g., `velocity`
+/// in the namespace `/turtlebot0` becomes `/turtlebot0/velocity`. Names that
+/// start with a slash are absolute and ignore the namespace.
+pub fn resolve_name(namespace: &str, name: &str) -> Result<String, Error> {
+    let resolved = match name.starts_with('/') {
+        true => name.to_owned(),
+        false => format!("{}/{name}", namespace.trim_end_matches('/')),
+    };
+    // names are made of tokens separated by slashes, tokens consist of letters,
+    // digits and underscores and do not start with a digit
+    let valid = resolved.strip_prefix('/')
+        .is_some_and(|tokens| tokens.split('/').all(|token| {
+            token.starts_with(|character: char| !character.is_ascii_digit()) &&
+            token.chars().all(|character| character.is_ascii_alphanumeric() || character == '_')
+        }));
+    match valid {
+        true => Ok(resolved),
+        false => Err(Error::InvalidTopicName(name.to_owned())),
+    }
+}
+
+/// A publisher or subscription that belongs to a node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    pub id: u64,
+    pub node: String,
+}
+
+/// A snapshot of a topic and the nodes that are connected to it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopicInfo {
+    pub name: String,
+    pub message_type: MessageType,
+    pub publishers: Vec<Endpoint>,
+    pub subscriptions: Vec<Endpoint>,
+}
+
+/// A snapshot of a parameter that was declared by a node
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterInfo {
+    pub node: String,
+    pub name: String,
+    pub value: ParameterValue,
+}
+
+// only the most recent `depth` messages are kept, like the KEEP_LAST history policy in ROS
+struct Queue {
+    depth: usize,
+    messages: VecDeque<Message>,
+    waker: Option<Waker>,
+}
+
+// monitors that are not drained, e.g., of a panel that is hidden, keep at most
+// this many messages and drop the oldest ones
+const MONITOR_DEPTH: usize = 10_000;
+
+type MonitorQueue = Rc<RefCell<VecDeque<(f64, String, Message)>>>;
+
+struct Topic {
+    message_type: MessageType,
+    publishers: Vec<Endpoint>,
+    subscriptions: Vec<(Endpoint, Rc<RefCell<Queue>>)>,
+}
+
+/// Named topics that deliver messages from publishers to all subscriptions. A
+/// topic exists while it has at least one publisher or subscription and its
+/// type is set by whichever of them was created first.
+#[derive(Default)]
+pub struct Bus {
+    topics: RefCell<BTreeMap<String, Topic>>,
+    monitors: RefCell<Vec<(u64, MonitorQueue)>>,
+    // by the name of the node that declared them and their own name
+    parameters: RefCell<BTreeMap<(String, String), ParameterValue>>,
+    next_id: Cell<u64>,
+    time: Cell<f64>,
+}
+
+impl Bus {
+    /// Sets the simulated time in seconds that monitors see messages arrive at
+    pub fn set_time(&self, time: f64) {
+        self.time.set(time);
+    }
+
+    pub fn time(&self) -> f64 {
+        self.time.get()
+    }
+
+    fn next_id(&self) -> u64 {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        id
+    }
+
+    fn connect(&self, topic: &str, message_type: MessageType) -> Result<u64, Error> {
+        let mut topics = self.topics.borrow_mut();
+        let topic_entry = topics.entry(topic.to_owned())
+            .or_insert_with(|| Topic {
+                message_type,
+                publishers: Vec::new(),
+                subscriptions: Vec::new(),
+            });
+        if topic_entry.message_type != message_type {
+            return Err(Error::TypeMismatch {
+                topic: topic.to_owned(),
+                expected: topic_entry.message_type,
+                found: message_type,
+            });
+        }
+        Ok(self.next_id())
+    }
+
+    fn disconnect(&self, topic: &str, id: u64) {
+        let mut topics = self.topics.borrow_mut();
+        if let Some(topic_entry) = topics.get_mut(topic) {
+            topic_entry.publishers.retain(|endpoint| endpoint.id != id);
+            topic_entry.subscriptions.retain(|(endpoint, _)| endpoint.id != id);
+            if topic_entry.publishers.is_empty() && topic_entry.subscriptions.is_empty() {
+                topics.remove(topic);
+            }
+        }
+    }
+
+    /// Creates a publisher for `node`, relative topic names are resolved against the root namespace
+    pub fn create_publisher(self: &Rc<Self>, node: &str, topic: &str, message_type: MessageType) -> Result<Publisher, Error> {
+        let topic = resolve_name("/", topic)?;
+        let id = self.connect(&topic, message_type)?;
+        self.topics.borrow_mut()
+            .get_mut(&topic)
+            .unwrap()
+            .publishers
+            .push(Endpoint { id, node: node.to_owned() });
+        Ok(Publisher {
+            bus: Rc::downgrade(self),
+            id,
+            topic,
+            message_type,
+        })
+    }
+
+    /// Creates a subscription for `node`, relative topic names are resolved
+    /// against the root namespace. At most `depth` messages are queued, older
+    /// messages are dropped when the queue is full.
+    pub fn create_subscription(
+        self: &Rc<Self>,
+        node: &str,
+        topic: &str,
+        message_type: MessageType,
+        depth: usize
+    ) -> Result<Subscription, Error> {
+        let topic = resolve_name("/", topic)?;
+        let id = self.connect(&topic, message_type)?;
+        let queue = Rc::new(RefCell::new(Queue {
+            depth: depth.max(1),
+            messages: VecDeque::new(),
+            waker: None,
+        }));
+        self.topics.borrow_mut()
+            .get_mut(&topic)
+            .unwrap()
+            .subscriptions
+            .push((Endpoint { id, node: node.to_owned() }, queue.clone()));
+        Ok(Subscription {
+            bus: Rc::downgrade(self),
+            id,
+            topic,
+            queue,
+        })
+    }
+
+    /// Creates a monitor that receives the messages published on all topics
+    pub fn create_monitor(self: &Rc<Self>) -> Monitor {
+        let id = self.next_id();
+        let queue = MonitorQueue::default();
+        self.monitors.borrow_mut().push((id, queue.clone()));
+        Monitor {
+            bus: Rc::downgrade(self),
+            id,
+            queue,
+        }
+    }
+
+    fn deliver(&self, topic: &str, message: &Message) {
+        for (_, queue) in self.monitors.borrow().iter() {
+            let mut queue = queue.borrow_mut();
+            if queue.len() == MONITOR_DEPTH {
+                queue.pop_front();
+            }
+            queue.push_back((self.time.get(), topic.to_owned(), message.clone()));
+        }
+        let queues = self.topics.borrow()
+            .get(topic)
+            .map(|topic| topic.subscriptions.iter()
+                .map(|(_, queue)| queue.clone())
+                .collect::<Vec<_>>())
+            .unwrap_or_default();
+        // the topics are no longer borrowed, so woken tasks are free to use the bus
+        for queue in queues {
+            let waker = {
+                let mut queue = queue.borrow_mut();
+                if queue.messages.len() == queue.depth {
+                    queue.messages.pop_front();
+                }
+                queue.messages.push_back(message.clone());
+                queue.waker.take()
+            };
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+    }
+
+    /// Returns a snapshot of all topics sorted by name
+    pub fn topics(&self) -> Vec<TopicInfo> {
+        self.topics.borrow()
+            .iter()
+            .map(|(name, topic)| TopicInfo {
+                name: name.clone(),
+                message_type: topic.message_type,
+                publishers: topic.publishers.clone(),
+                subscriptions: topic.subscriptions.iter()
+                    .map(|(endpoint, _)| endpoint.clone())
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Declares a parameter of `node` that keeps its type but can be changed
+    /// with [Bus::set_parameter] until the returned handle is dropped
+    pub fn declare_parameter(self: &Rc<Self>, node: &str, name: &str, value: ParameterValue) -> Result<Parameter, Error> {
+        if !parameter::is_valid_name(name) {
+            return Err(Error::InvalidParameterName(name.to_owned()));
+        }
+        let key = (node.to_owned(), name.to_owned());
+        let mut parameters = self.parameters.borrow_mut();
+        if parameters.contains_key(&key) {
+            return Err(Error::ParameterAlreadyDeclared { node: key.0, name: key.1 });
+        }
+        parameters.insert(key.clone(), value);
+        Ok(Parameter {
+            bus: Rc::downgrade(self),
+            key,
+        })
+    }
+
+    /// Changes the value of a declared parameter, which must keep its type
+    pub fn set_parameter(&self, node: &str, name: &str, value: ParameterValue) -> Result<(), Error> {
+        let mut parameters = self.parameters.borrow_mut();
+        let current = parameters.get_mut(&(node.to_owned(), name.to_owned()))
+            .ok_or_else(|| Error::ParameterNotDeclared { node: node.to_owned(), name: name.to_owned() })?;
+        let found = value.type_name();
+        *current = value.coerce_to(current)
+            .ok_or_else(|| Error::ParameterTypeMismatch { name: name.to_owned(), expected: current.type_name(), found })?;
+        Ok(())
+    }
+
+    /// Returns a snapshot of all parameters sorted by node and name
+    pub fn parameters(&self) -> Vec<ParameterInfo> {
+        self.parameters.borrow()
+            .iter()
+            .map(|((node, name), value)| ParameterInfo {
+                node: node.clone(),
+                name: name.clone(),
+                value: value.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Publishes messages on a topic until it is dropped
+pub struct Publisher {
+    bus: Weak<Bus>,
+    id: u64,
+    topic: String,
+    message_type: MessageType,
+}
+
+impl Publisher {
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    pub fn publish(&self, message: impl Into<Message>) -> Result<(), Error> {
+        let message = message.into();
+        if message.message_type() != self.message_type {
+            return Err(Error::TypeMismatch {
+                topic: self.topic.clone(),
+                expected: self.message_type,
+                found: message.message_type(),
+            });
+        }
+        if let Some(bus) = self.bus.upgrade() {
+            bus.deliver(&self.topic, &message);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Publisher {
+    fn drop(&mut self) {
+        if let Some(bus) = self.bus.upgrade() {
+            bus.disconnect(&self.topic, self.id);
+        }
+    }
+}
+
+/// Receives the messages published on a topic until it is dropped. Messages
+/// can either be drained with [Subscription::drain] or awaited as a [Stream].
+pub struct Subscription {
+    bus: Weak<Bus>,
+    id: u64,
+    topic: String,
+    queue: Rc<RefCell<Queue>>,
+}
+
+impl Subscription {
+    /// Takes all queued messages, oldest first
+    pub fn drain(&self) -> Vec<Message> {
+        self.queue.borrow_mut().messages.drain(..).collect()
+    }
+}
+
+impl Stream for Subscription {
+    type Item = Message;
+
+    fn poll_next(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Option<Message>> {
+        let mut queue = self.queue.borrow_mut();
+        match queue.messages.pop_front() {
+            Some(message) => Poll::Ready(Some(message)),
+            None => {
+                queue.waker = Some(context.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        if let Some(bus) = self.bus.upgrade() {
+            bus.disconnect(&self.topic, self.id);
+        }
+    }
+}
+
+/// A parameter that was declared by a node, it is removed when this is dropped
+pub struct Parameter {
+    bus: Weak<Bus>,
+    key: (String, String),
+}
+
+impl Parameter {
+    pub fn node(&self) -> &str {
+        &self.key.0
+    }
+
+    pub fn name(&self) -> &str {
+        &self.key.1
+    }
+
+    /// The current value, which may have been changed since it was declared
+    pub fn value(&self) -> Option<ParameterValue> {
+        self.bus.upgrade()
+            .and_then(|bus| bus.parameters.borrow().get(&self.key).cloned())
+    }
+}
+
+impl Drop for Parameter {
+    fn drop(&mut self) {
+        if let Some(bus) = self.bus.upgrade() {
+            bus.parameters.borrow_mut().remove(&self.key);
+        }
+    }
+}
+
+/// Receives the messages published on all topics until it is dropped, which
+/// is useful for tools that record or inspect the traffic on the bus
+pub struct Monitor {
+    bus: Weak<Bus>,
+    id: u64,
+    queue: MonitorQueue,
+}
+
+impl Monitor {
+    /// Takes all messages published since the last call along with the time
+    /// they were published at and their topics, oldest first
+    pub fn drain(&self) -> Vec<(f64, String, Message)> {
+        self.queue.borrow_mut().drain(..).collect()
+    }
+}
+
+impl Drop for Monitor {
+    fn drop(&mut self) {
+        if let Some(bus) = self.bus.upgrade() {
+            bus.monitors.borrow_mut().retain(|(id, _)| *id != self.id);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// The value of a node parameter, its type is fixed once it is declared. The
+/// type is serialized along with the value since JSON does not tell `5.0` from `5`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value")]
+pub enum ParameterValue {
+    #[serde(rename = "bool")]
+    Bool(bool),
+    #[serde(rename = "int")]
+    Integer(i64),
+    #[serde(rename = "double")]
+    Double(f64),
+    #[serde(rename = "str")]
+    String(String),
+}
+
+impl ParameterValue {
+    /// The name of the type as used by the `type` attribute in launch files
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            ParameterValue::Bool(_) => "bool",
+            ParameterValue::Integer(_) => "int",
+            ParameterValue::Double(_) => "double",
+            ParameterValue::String(_) => "str",
+        }
+    }
+
+    /// Infers the type from the text like ROS does for launch files, i.e.,
+    /// `true` is a bool, `5` an integer, `5.0` a double and anything else a string
+    pub fn parse(text: &str) -> ParameterValue {
+        let trimmed = text.trim();
+        match trimmed {
+            "true" | "True" => ParameterValue::Bool(true),
+            "false" | "False" => ParameterValue::Bool(false),
+            _ => trimmed.parse::<i64>().map(ParameterValue::Integer)
+                .or_else(|_| trimmed.parse::<f64>().map(ParameterValue::Double))
+                .ok()
+                .filter(|value| !matches!(value, ParameterValue::Double(double) if !double.is_finite()))
+                .unwrap_or_else(|| ParameterValue::String(text.to_owned())),
+        }
+    }
+
+    /// Parses the text as a value of the type called `type_name`
+    pub fn parse_as(type_name: &str, text: &str) -> Option<ParameterValue> {
+        let trimmed = text.trim();
+        match type_name {
+            "bool" => match trimmed {
+                "true" | "True" => Some(ParameterValue::Bool(true)),
+                "false" | "False" => Some(ParameterValue::Bool(false)),
+                _ => None,
+            },
+            "int" => trimmed.parse().ok().map(ParameterValue::Integer),
+            "double" => trimmed.parse::<f64>().ok()
+                .filter(|value| value.is_finite())
+                .map(ParameterValue::Double),
+            "str" => Some(ParameterValue::String(text.to_owned())),
+            _ => None,
+        }
+    }
+
+    /// Converts the value to the type of `other`, integers are accepted where a
+    /// double is expected since `5` is easily written instead of `5.0`
+    pub fn coerce_to(self, other: &ParameterValue) -> Option<ParameterValue> {
+        match (self, other) {
+            (ParameterValue::Integer(value), ParameterValue::Double(_)) => Some(ParameterValue::Double(value as f64)),
+            (value, other) if value.type_name() == other.type_name() => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ParameterValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParameterValue::Bool(value) => write!(f, "{value}"),
+            ParameterValue::Integer(value) => write!(f, "{value}"),
+            ParameterValue::Double(value) => write!(f, "{value:?}"),
+            ParameterValue::String(value) => f.write_str(value),
+        }
+    }
+}
+
+/// Checks that a parameter name consists of tokens separated by dots, which
+/// are made of letters, digits and underscores and do not start with a digit
+pub fn is_valid_name(name: &str) -> bool {
+    name.split('.').all(|token| {
+        token.starts_with(|character: char| character.is_ascii_alphabetic() || character == '_') &&
+        token.chars().all(|character| character.is_ascii_alphanumeric() || character == '_')
+    })
+}
@@ -0,0 +1,449 @@
+use std::{collections::HashSet, fmt};
+
+use roxmltree::{Document, Node as Element};
+
+use crate::bus::{parameter, ParameterValue};
+
+pub mod scenario;
+
+/// A parsed launch file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Launch {
+    pub nodes: Vec<Node>,
+    pub world: World,
+}
+
+/// A node that is started from an executable inside of a package
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub pkg: String,
+    pub exec: String,
+    pub parameters: Vec<Parameter>,
+}
+
+/// A value that overrides the default of a parameter that the node declares
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+    pub name: String,
+    pub value: ParameterValue,
+}
+
+/// The world is centered on the origin, the y axis points upwards and `size`
+/// is the extent of the ground plane along the x and z axes in meters
+#[derive(Debug, Clone, PartialEq)]
+pub struct World {
+    pub size: [f64; 2],
+    pub collision: Collision,
+    /// Whether the odometry of the robots is subject to noise and drift
+    pub noise: bool,
+    pub models: Vec<Model>,
+}
+
+/// How robots react when they run into the boundary of the world or another model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Collision {
+    /// The robot stays where it was and its wheels stop until the next command
+    Stop,
+    /// The robot is pushed out of the obstacle, so it slides along its surface
+    #[default]
+    Slide,
+    /// The robot is pushed out of the obstacle and its heading is reflected
+    Bounce,
+}
+
+impl Collision {
+    pub const ALL: [Collision; 3] = [Collision::Stop, Collision::Slide, Collision::Bounce];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Collision::Stop => "stop",
+            Collision::Slide => "slide",
+            Collision::Bounce => "bounce",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ModelType {
+    Turtlebot,
+    /// A static obstacle
+    Box,
+}
+
+impl ModelType {
+    pub fn name(self) -> &'static str {
+        match self {
+            ModelType::Turtlebot => "turtlebot",
+            ModelType::Box => "box",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Model {
+    /// Defaults to the type followed by its index amongst models of the same type, e.g., `turtlebot0`
+    pub name: String,
+    pub model_type: ModelType,
+    pub pose: Pose,
+    /// The extent along the x and z axes before rotation, only boxes have a size
+    pub size: Option<[f64; 2]>,
+    /// Only turtlebots carry sensors
+    pub sensors: Vec<Sensor>,
+}
+
+/// A sensor that measures distances along rays starting at the center of its
+/// model and publishes them on `/<model>/<name>` every `1 / rate` seconds
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sensor {
+    /// Defaults to `scan` for a lidar and to `range` followed by its index for range sensors
+    pub name: String,
+    pub sensor_type: SensorType,
+    /// The number of measurements per second
+    pub rate: f64,
+    /// The maximum distance in meters that can be measured
+    pub range: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SensorType {
+    /// A single ray at `angle` radians relative to the heading of the model
+    Range { angle: f64 },
+    /// `samples` rays spread evenly around the model, starting at its heading
+    Lidar { samples: u32 },
+}
+
+impl SensorType {
+    pub fn name(self) -> &'static str {
+        match self {
+            SensorType::Range { .. } => "range",
+            SensorType::Lidar { .. } => "lidar",
+        }
+    }
+}
+
+pub const DEFAULT_RANGE_RATE: f64 = 10.0;
+pub const DEFAULT_RANGE_RANGE: f64 = 2.0;
+/// The defaults of the lidar match the LDS-01 of the TurtleBot3
+pub const DEFAULT_LIDAR_RATE: f64 = 5.0;
+pub const DEFAULT_LIDAR_RANGE: f64 = 3.5;
+pub const DEFAULT_LIDAR_SAMPLES: u32 = 360;
+pub const MAX_LIDAR_SAMPLES: u32 = 3600;
+
+/// A position followed by the rotations about the x, y and z axes in radians
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Pose {
+    pub position: [f64; 3],
+    pub orientation: [f64; 3],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub line: u32,
+    pub column: u32,
+    pub message: String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+fn document(source: &str) -> Result<Document<'_>, Error> {
+    Document::parse(source)
+        .map_err(|error| Error {
+            line: error.pos().row,
+            column: error.pos().col,
+            message: error.to_string(),
+        })
+}
+
+pub fn parse(source: &str) -> Result<Launch, Error> {
+    let document = document(source)?;
+    Parser { document: &document }.parse_launch(document.root_element())
+}
+
+struct Parser<'a, 'input> {
+    document: &'a Document<'input>,
+}
+
+impl<'a, 'input> Parser<'a, 'input> {
+    fn error(&self, position: usize, message: impl Into<String>) -> Error {
+        let position = self.document.text_pos_at(position);
+        Error {
+            line: position.row,
+            column: position.col,
+            message: message.into(),
+        }
+    }
+
+    fn element_error(&self, element: Element, message: impl Into<String>) -> Error {
+        self.error(element.range().start, message)
+    }
+
+    // rejects unknown attributes so that typos do not go unnoticed
+    fn check_attributes(&self, element: Element, allowed: &[&str]) -> Result<(), Error> {
+        match element.attributes().find(|attribute| !allowed.contains(&attribute.name())) {
+            Some(attribute) => Err(self.error(attribute.position(),
+                format!("unexpected attribute `{}` on <{}>", attribute.name(), element.tag_name().name()))),
+            None => Ok(())
+        }
+    }
+
+    fn children<'b>(&self, element: Element<'b, 'input>, allowed: &[&str]) -> Result<Vec<Element<'b, 'input>>, Error> {
+        element.children()
+            .filter(Element::is_element)
+            .map(|child| match allowed.contains(&child.tag_name().name()) {
+                true => Ok(child),
+                false => Err(self.element_error(child,
+                    format!("unexpected element <{}> in <{}>", child.tag_name().name(), element.tag_name().name()))),
+            })
+            .collect()
+    }
+
+    fn attribute<'b>(&self, element: Element<'b, 'input>, name: &str) -> Result<&'b str, Error> {
+        element.attribute(name)
+            .ok_or_else(|| self.element_error(element,
+                format!("<{}> is missing the attribute `{name}`", element.tag_name().name())))
+    }
+
+    fn attribute_position(&self, element: Element, name: &str) -> usize {
+        element.attributes()
+            .find(|attribute| attribute.name() == name)
+            .map(|attribute| attribute.position())
+            .unwrap_or(element.range().start)
+    }
+
+    fn values<const N: usize>(&self, element: Element, name: &str) -> Result<[f64; N], Error> {
+        let value = self.attribute(element, name)?;
+        let invalid = || self.error(self.attribute_position(element, name),
+            format!("`{name}` must be {N} numbers separated by spaces"));
+        let values = value.split_whitespace()
+            .map(|value| value.parse::<f64>().ok().filter(|value| value.is_finite()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(invalid)?;
+        values.try_into().map_err(|_| invalid())
+    }
+
+    fn parse_launch(&self, element: Element) -> Result<Launch, Error> {
+        if element.tag_name().name() != "launch" {
+            return Err(self.element_error(element, "the root element must be <launch>"));
+        }
+        self.check_attributes(element, &[])?;
+        let mut nodes = Vec::new();
+        let mut world = None;
+        for child in self.children(element, &["node", "world"])? {
+            match child.tag_name().name() {
+                "node" => nodes.push(self.parse_node(child)?),
+                _ => match world {
+                    None => world = Some(self.parse_world(child)?),
+                    Some(_) => return Err(self.element_error(child, "<launch> can only contain one <world>")),
+                }
+            }
+        }
+        let world = world
+            .ok_or_else(|| self.element_error(element, "<launch> is missing a <world>"))?;
+        Ok(Launch { nodes, world })
+    }
+
+    fn parse_node(&self, element: Element) -> Result<Node, Error> {
+        self.check_attributes(element, &["pkg", "exec"])?;
+        let mut parameters: Vec<Parameter> = Vec::new();
+        for child in self.children(element, &["param"])? {
+            let parameter = self.parse_parameter(child)?;
+            if parameters.iter().any(|other| other.name == parameter.name) {
+                return Err(self.element_error(child, format!("the parameter `{}` is already set", parameter.name)));
+            }
+            parameters.push(parameter);
+        }
+        Ok(Node {
+            pkg: self.attribute(element, "pkg")?.to_owned(),
+            exec: self.attribute(element, "exec")?.to_owned(),
+            parameters,
+        })
+    }
+
+    // the type is inferred from the value unless it is given explicitly
+    fn parse_parameter(&self, element: Element) -> Result<Parameter, Error> {
+        self.check_attributes(element, &["name", "value", "type"])?;
+        self.children(element, &[])?;
+        let name = self.attribute(element, "name")?;
+        if !parameter::is_valid_name(name) {
+            return Err(self.error(self.attribute_position(element, "name"),
+                "`name` may only contain letters, digits, underscores and dots"));
+        }
+        let text = self.attribute(element, "value")?;
+        let value = match element.attribute("type") {
+            Some(type_name @ ("bool" | "int" | "double" | "str")) => ParameterValue::parse_as(type_name, text)
+                .ok_or_else(|| self.error(self.attribute_position(element, "value"),
+                    format!("`value` must be of type `{type_name}`")))?,
+            Some(_) => return Err(self.error(self.attribute_position(element, "type"),
+                "`type` must be `bool`, `int`, `double` or `str`")),
+            None => ParameterValue::parse(text),
+        };
+        Ok(Parameter {
+            name: name.to_owned(),
+            value,
+        })
+    }
+
+    fn parse_world(&self, element: Element) -> Result<World, Error> {
+        self.check_attributes(element, &["size", "collision", "noise"])?;
+        let size = self.size(element)?;
+        let collision = match element.attribute("collision") {
+            Some(name) => Collision::ALL.into_iter()
+                .find(|collision| collision.name() == name)
+                .ok_or_else(|| self.error(self.attribute_position(element, "collision"),
+                    "`collision` must be `stop`, `slide` or `bounce`"))?,
+            None => Collision::default(),
+        };
+        let noise = match element.attribute("noise") {
+            Some("true") | None => true,
+            Some("false") => false,
+            Some(_) => return Err(self.error(self.attribute_position(element, "noise"),
+                "`noise` must be `true` or `false`")),
+        };
+        let mut models: Vec<Model> = Vec::new();
+        let mut names = HashSet::new();
+        for child in self.children(element, &["model"])? {
+            let model_type = self.model_type(child)?;
+            let index = models.iter()
+                .filter(|model| model.model_type == model_type)
+                .count();
+            let model = self.parse_model(child, model_type, index)?;
+            if !names.insert(model.name.clone()) {
+                return Err(self.element_error(child, format!("a model called `{}` already exists", model.name)));
+            }
+            models.push(model);
+        }
+        Ok(World { size, collision, noise, models })
+    }
+
+    // parses an optional number, which has to be positive unless it may be `negative`
+    fn value(&self, element: Element, name: &str, default: f64, negative: bool) -> Result<f64, Error> {
+        match element.attribute(name) {
+            Some(value) => self.number(element, name, value, negative),
+            None => Ok(default),
+        }
+    }
+
+    // like [Parser::value] for a number that has no default
+    fn required_value(&self, element: Element, name: &str, negative: bool) -> Result<f64, Error> {
+        let value = self.attribute(element, name)?;
+        self.number(element, name, value, negative)
+    }
+
+    fn number(&self, element: Element, name: &str, value: &str, negative: bool) -> Result<f64, Error> {
+        let position = self.attribute_position(element, name);
+        let value = value.trim().parse::<f64>().ok()
+            .filter(|value| value.is_finite())
+            .ok_or_else(|| self.error(position, format!("`{name}` must be a number")))?;
+        match negative || value > 0.0 {
+            true => Ok(value),
+            false => Err(self.error(position, format!("`{name}` must be positive"))),
+        }
+    }
+
+    fn name(&self, element: Element) -> Result<Option<String>, Error> {
+        match element.attribute("name") {
+            Some(name) if name.is_empty() || name.contains(|character: char| !(character.is_ascii_alphanumeric() || character == '_')) =>
+                Err(self.error(self.attribute_position(element, "name"),
+                    "`name` may only contain letters, digits and underscores")),
+            name => Ok(name.map(str::to_owned)),
+        }
+    }
+
+    fn size(&self, element: Element) -> Result<[f64; 2], Error> {
+        let size: [f64; 2] = self.values(element, "size")?;
+        match size.iter().all(|extent| *extent > 0.0) {
+            true => Ok(size),
+            false => Err(self.error(self.attribute_position(element, "size"), "`size` must be positive")),
+        }
+    }
+
+    fn model_type(&self, element: Element) -> Result<ModelType, Error> {
+        match self.attribute(element, "type")? {
+            "turtlebot" => Ok(ModelType::Turtlebot),
+            "box" => Ok(ModelType::Box),
+            other => Err(self.error(self.attribute_position(element, "type"),
+                format!("unknown model type `{other}`"))),
+        }
+    }
+
+    fn parse_model(&self, element: Element, model_type: ModelType, index: usize) -> Result<Model, Error> {
+        let size = match model_type {
+            ModelType::Turtlebot => {
+                self.check_attributes(element, &["name", "type", "pose"])?;
+                None
+            }
+            ModelType::Box => {
+                self.check_attributes(element, &["name", "type", "pose", "size"])?;
+                Some(self.size(element)?)
+            }
+        };
+        let sensors = match model_type {
+            ModelType::Turtlebot => self.parse_sensors(element)?,
+            ModelType::Box => {
+                self.children(element, &[])?;
+                Vec::new()
+            }
+        };
+        let [x, y, z, rx, ry, rz] = self.values(element, "pose")?;
+        let name = self.name(element)?
+            .unwrap_or_else(|| format!("{}{index}", model_type.name()));
+        Ok(Model {
+            name,
+            model_type,
+            pose: Pose {
+                position: [x, y, z],
+                orientation: [rx, ry, rz],
+            },
+            size,
+            sensors,
+        })
+    }
+
+    fn parse_sensors(&self, element: Element) -> Result<Vec<Sensor>, Error> {
+        let mut sensors: Vec<Sensor> = Vec::new();
+        for child in self.children(element, &["range", "lidar"])? {
+            let sensor = match child.tag_name().name() {
+                "range" => {
+                    self.check_attributes(child, &["name", "rate", "range", "angle"])?;
+                    let index = sensors.iter()
+                        .filter(|sensor| matches!(sensor.sensor_type, SensorType::Range { .. }))
+                        .count();
+                    Sensor {
+                        name: self.name(child)?.unwrap_or_else(|| format!("range{index}")),
+                        sensor_type: SensorType::Range { angle: self.value(child, "angle", 0.0, true)? },
+                        rate: self.value(child, "rate", DEFAULT_RANGE_RATE, false)?,
+                        range: self.value(child, "range", DEFAULT_RANGE_RANGE, false)?,
+                    }
+                }
+                _ => {
+                    self.check_attributes(child, &["name", "rate", "range", "samples"])?;
+                    let samples = match child.attribute("samples") {
+                        Some(samples) => samples.trim().parse::<u32>().ok()
+                            .filter(|samples| (1..=MAX_LIDAR_SAMPLES).contains(samples))
+                            .ok_or_else(|| self.error(self.attribute_position(child, "samples"),
+                                format!("`samples` must be an integer from 1 to {MAX_LIDAR_SAMPLES}")))?,
+                        None => DEFAULT_LIDAR_SAMPLES,
+                    };
+                    Sensor {
+                        name: self.name(child)?.unwrap_or_else(|| "scan".to_owned()),
+                        sensor_type: SensorType::Lidar { samples },
+                        rate: self.value(child, "rate", DEFAULT_LIDAR_RATE, false)?,
+                        range: self.value(child, "range", DEFAULT_LIDAR_RANGE, false)?,
+                    }
+                }
+            };
+            self.children(child, &[])?;
+            if sensors.iter().any(|other| other.name == sensor.name) {
+                return Err(self.element_error(child, format!("a sensor called `{}` already exists", sensor.name)));
+            }
+            sensors.push(sensor);
+        }
+        Ok(sensors)
+    }
+}
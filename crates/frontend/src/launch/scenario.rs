@@ -0,0 +1,153 @@
+use roxmltree::Node as Element;
+
+use super::{Error, Parser};
+
+// the distance in meters within which a position counts as reached
+const DEFAULT_TOLERANCE: f64 = 0.1;
+
+/// Assertions about the behavior of the robots that are checked while the
+/// simulation runs for `duration` seconds
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scenario {
+    pub duration: f64,
+    pub assertions: Vec<Assertion>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Assertion {
+    /// The robot called `model` gets within `tolerance` meters of `position`
+    /// on the ground plane, i.e., x and z, before `within` seconds have passed
+    Reach {
+        model: String,
+        position: [f64; 2],
+        tolerance: f64,
+        within: f64,
+    },
+    /// The robot called `model`, or any robot if it is none, never touches the
+    /// boundary of the world or another model
+    AvoidCollisions {
+        model: Option<String>,
+    },
+}
+
+impl Assertion {
+    /// The name of the model that the assertion refers to, if any
+    pub fn model(&self) -> Option<&str> {
+        match self {
+            Assertion::Reach { model, .. } => Some(model),
+            Assertion::AvoidCollisions { model } => model.as_deref(),
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            Assertion::Reach { model, position: [x, z], tolerance, within } =>
+                format!("{model} reaches within {tolerance} m of ({x}, {z}) within {within} s"),
+            Assertion::AvoidCollisions { model: Some(model) } => format!("{model} does not collide"),
+            Assertion::AvoidCollisions { model: None } => "no robot collides".to_owned(),
+        }
+    }
+}
+
+/// Parses a scenario, e.g.,
+///
+/// ```xml
+/// <scenario duration="30">
+///   <reach model="turtlebot0" position="1.0 2.0" tolerance="0.2" />
+///   <avoid_collisions />
+/// </scenario>
+/// ```
+pub fn parse(source: &str) -> Result<Scenario, Error> {
+    let document = super::document(source)?;
+    Parser { document: &document }.parse_scenario(document.root_element())
+}
+
+impl<'a, 'input> Parser<'a, 'input> {
+    fn parse_scenario(&self, element: Element) -> Result<Scenario, Error> {
+        if element.tag_name().name() != "scenario" {
+            return Err(self.element_error(element, "the root element must be <scenario>"));
+        }
+        self.check_attributes(element, &["duration"])?;
+        let duration = self.required_value(element, "duration", false)?;
+        let assertions = self.children(element, &["reach", "avoid_collisions"])?
+            .into_iter()
+            .map(|child| self.parse_assertion(child, duration))
+            .collect::<Result<Vec<_>, _>>()?;
+        if assertions.is_empty() {
+            return Err(self.element_error(element, "<scenario> does not contain any assertions"));
+        }
+        Ok(Scenario { duration, assertions })
+    }
+
+    fn parse_assertion(&self, element: Element, duration: f64) -> Result<Assertion, Error> {
+        self.children(element, &[])?;
+        match element.tag_name().name() {
+            "reach" => {
+                self.check_attributes(element, &["model", "position", "tolerance", "within"])?;
+                let within = self.value(element, "within", duration, false)?;
+                if within > duration {
+                    return Err(self.error(self.attribute_position(element, "within"),
+                        format!("`within` must not exceed the duration of {duration} s")));
+                }
+                Ok(Assertion::Reach {
+                    model: self.attribute(element, "model")?.to_owned(),
+                    position: self.values(element, "position")?,
+                    tolerance: self.value(element, "tolerance", DEFAULT_TOLERANCE, false)?,
+                    within,
+                })
+            }
+            _ => {
+                self.check_attributes(element, &["model"])?;
+                Ok(Assertion::AvoidCollisions {
+                    model: element.attribute("model").map(str::to_owned),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_assertions() {
+        let scenario = parse(r#"<scenario duration="30">
+            <reach model="turtlebot0" position="1.0 -2.0" within="20" />
+            <avoid_collisions />
+            <avoid_collisions model="turtlebot0" />
+        </scenario>"#).unwrap();
+        assert_eq!(scenario, Scenario {
+            duration: 30.0,
+            assertions: vec![
+                Assertion::Reach {
+                    model: "turtlebot0".to_owned(),
+                    position: [1.0, -2.0],
+                    tolerance: DEFAULT_TOLERANCE,
+                    within: 20.0,
+                },
+                Assertion::AvoidCollisions { model: None },
+                Assertion::AvoidCollisions { model: Some("turtlebot0".to_owned()) },
+            ],
+        });
+    }
+
+    #[test]
+    fn within_defaults_to_duration() {
+        let scenario = parse(r#"<scenario duration="10"><reach model="a" position="0 0" /></scenario>"#).unwrap();
+        assert!(matches!(scenario.assertions[0], Assertion::Reach { within, .. } if within == 10.0));
+    }
+
+    #[test]
+    fn reject_invalid_scenarios() {
+        let error = |source: &str| parse(source).unwrap_err().to_string();
+        assert_eq!(error(r#"<scenario duration="5"><reach model="a" position="1 0" within="6" /></scenario>"#),
+            "1:56: `within` must not exceed the duration of 5 s");
+        assert_eq!(error(r#"<scenario><avoid_collisions /></scenario>"#),
+            "1:1: <scenario> is missing the attribute `duration`");
+        assert_eq!(error(r#"<scenario duration="5"></scenario>"#),
+            "1:1: <scenario> does not contain any assertions");
+        assert_eq!(error(r#"<launch />"#),
+            "1:1: the root element must be <scenario>");
+    }
+}
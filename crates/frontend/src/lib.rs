@@ -2,7 +2,7 @@ use std::{rc::Rc, sync::Arc};
 
 use dominator_bulma::{column, columns};
 use futures::channel::mpsc;
-use futures_signals::{map_ref, signal::SignalExt, signal_vec::MutableVec};
+use futures_signals::{map_ref, signal::{Mutable, SignalExt}, signal_vec::MutableVec};
 use once_cell::sync::Lazy;
 use tracing_subscriber::{prelude::*, EnvFilter};
 use wasm_bindgen::prelude::*;
@@ -11,9 +11,22 @@ mod sidebar;
 mod workspace;
 mod vfs;
 mod contextmenu;
+mod persistence;
+mod launch;
+mod simulation;
+mod bus;
+mod python;
+mod log;
+mod bag;
+mod package;
+mod protocol;
 
 enum WorkspaceCommand {
     OpenFile(Rc<vfs::File>),
+    OpenSimulator,
+    OpenGraph,
+    OpenPlot,
+    OpenResults,
 }
 type WorkspaceCommandSender = mpsc::UnboundedSender<WorkspaceCommand>;
 type WorkspaceCommandReceiver = mpsc::UnboundedReceiver<WorkspaceCommand>;
@@ -32,6 +45,13 @@ pub async fn main() {
     use sidebar::Sidebar;
     use workspace::Workspace;
 
+    match persistence::load().await {
+        Ok(Some(project)) => PROJECT.with(|current| current.replace(&project)),
+        Ok(None) => {}
+        Err(error) => tracing::error!("could not restore project: {error:?}"),
+    }
+    wasm_bindgen_futures::spawn_local(persistence::autosave());
+
     let (workspace_command_tx, workspace_command_rx) = mpsc::unbounded();
 
     let sidebar: Rc<Sidebar> = Default::default();
@@ -63,7 +83,11 @@ const DEFAULT_FILE_MODE: u32 = 0o664;
 const DEFAULT_DIRECTORY_MODE: u32 = 0o775;
 const LAUNCH_XML: &str = "\
 <launch>
-  <node pkg=\"velocity_control\" exec=\"run\" />
+  <node pkg=\"velocity_control\" exec=\"run\">
+    <param name=\"timer_period\" value=\"5.0\" />
+    <param name=\"forward_speed\" value=\"5.0\" />
+    <param name=\"turn_speed\" value=\"2.5\" />
+  </node>
   <world size=\"3.0 6.0\">
     <model type=\"turtlebot\" pose=\"-0.5 0.0 2.0 0 0.785 0\" />
     <model type=\"turtlebot\" pose=\"0.5 0.0 -2.0 0 1.57 0\" />
@@ -82,19 +106,36 @@ class VelocityPublisher(Node):
         super().__init__('velocity_publisher')
         self.publisher_ = \
             self.create_publisher(Velocity, 'velocity', 10)
-        timer_period = 5.0  # seconds
+        # the values in launch.xml take precedence over these defaults
+        self.declare_parameter('timer_period', 5.0)  # seconds
+        self.declare_parameter('forward_speed', 5.0)  # rad/s
+        self.declare_parameter('turn_speed', 2.5)  # rad/s
+        timer_period = self.get_parameter('timer_period').value
         self.timer = \
             self.create_timer(timer_period, self.timer_callback)
+        self.add_on_set_parameters_callback(self.parameters_callback)
         self.drive_forwards = True
+    def parameters_callback(self, parameters):
+        for parameter in parameters:
+            if parameter.name == 'timer_period':
+                if parameter.value <= 0:
+                    self.get_logger().warning('The timer period must be positive')
+                    continue
+                # restart the timer with the new period
+                self.destroy_timer(self.timer)
+                self.timer = \
+                    self.create_timer(parameter.value, self.timer_callback)
     def timer_callback(self):
         if self.drive_forwards:
             # drive forwards
+            speed = self.get_parameter('forward_speed').value
             self.get_logger().info('Driving forwards')
-            self.publisher_.publish(Velocity(left=5.0, right=-5.0))
+            self.publisher_.publish(Velocity(left=speed, right=-speed))
         else:
             # turn on the spot
+            speed = self.get_parameter('turn_speed').value
             self.get_logger().info('Turning')
-            self.publisher_.publish(Velocity(left=2.5, right=2.5))
+            self.publisher_.publish(Velocity(left=speed, right=speed))
         # toggle mode
         self.drive_forwards = not self.drive_forwards
         
@@ -108,31 +149,38 @@ rclpy.shutdown()
 thread_local! {
     pub static GLOBAL_LOG: Lazy<MutableVec<Arc<str>>> = Lazy::new(Default::default);
 
-    pub static PROJECT: Lazy<Rc<vfs::Directory>> = Lazy::new(|| {
-        vfs::Directory {
-            name: "project".to_owned().into(),
-            mode: Default::default(),
-            files: vec![
-                vfs::File {
-                    name: "launch.xml".to_owned().into(),
-                    mode: DEFAULT_FILE_MODE.into(),
-                    data: LAUNCH_XML.as_bytes().to_vec().into()
-                }.into(),
-            ].into(),
-            directories: vec![
-                vfs::Directory {
-                    name: "velocity_control".to_owned().into(),
-                    mode: DEFAULT_DIRECTORY_MODE.into(),
-                    directories: vec![].into(),
-                    files: vec![
-                        vfs::File {
-                            name: "run.py".to_owned().into(),
-                            mode: DEFAULT_FILE_MODE.into(),
-                            data: VELOCITY_CONTROL_PY.as_bytes().to_vec().into()
-                        }.into(),
-                    ].into()
-                }.into()
-            ].into(),
-        }.into()
-    });
-}
\ No newline at end of file
+    pub static PROJECT: Lazy<Rc<vfs::Directory>> = Lazy::new(|| default_project().into());
+
+    pub static BUS: Lazy<Rc<bus::Bus>> = Lazy::new(Default::default);
+
+    /// The outcomes of the scenario of the last simulation, if it had one
+    pub static REPORT: Lazy<Mutable<Option<Rc<simulation::scenario::Report>>>> = Lazy::new(Default::default);
+}
+
+fn default_project() -> vfs::Directory {
+    vfs::Directory {
+        name: "project".to_owned().into(),
+        mode: Default::default(),
+        files: vec![
+            vfs::File {
+                name: "launch.xml".to_owned().into(),
+                mode: DEFAULT_FILE_MODE.into(),
+                data: LAUNCH_XML.as_bytes().to_vec().into()
+            }.into(),
+        ].into(),
+        directories: vec![
+            vfs::Directory {
+                name: "velocity_control".to_owned().into(),
+                mode: DEFAULT_DIRECTORY_MODE.into(),
+                directories: vec![].into(),
+                files: vec![
+                    vfs::File {
+                        name: "run.py".to_owned().into(),
+                        mode: DEFAULT_FILE_MODE.into(),
+                        data: VELOCITY_CONTROL_PY.as_bytes().to_vec().into()
+                    }.into(),
+                ].into()
+            }.into()
+        ].into(),
+    }
+}
@@ -0,0 +1,67 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Level {
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Fatal,
+}
+
+impl Level {
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::Debug => "DEBUG",
+            Level::Info => "INFO",
+            Level::Warn => "WARN",
+            Level::Error => "ERROR",
+            Level::Fatal => "FATAL",
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The node name used for messages from the simulator itself
+pub const SIMULATOR: &str = "simulator";
+
+/// Formats a line like the console logger of ROS 2, i.e.,
+/// `[INFO] [1700000000.123456789] [node]: message`, where the timestamp is
+/// in seconds since the UNIX epoch
+pub fn format(level: Level, timestamp: f64, node: &str, message: &str) -> String {
+    let nanoseconds = (timestamp.max(0.0) * 1e9).round() as u128;
+    format!("[{level}] [{}.{:09}] [{node}]: {message}",
+        nanoseconds / 1_000_000_000, nanoseconds % 1_000_000_000)
+}
+
+/// Writes a message to the console, messages that span several lines are
+/// written as one entry per line so that each of them keeps its header
+pub fn log(level: Level, node: &str, message: &str) {
+    let timestamp = js_sys::Date::now() / 1000.0;
+    crate::GLOBAL_LOG.with(|log| {
+        let mut log = log.lock_mut();
+        for line in message.lines().filter(|line| !line.trim().is_empty()) {
+            log.push_cloned(format(level, timestamp, node, line).into());
+        }
+    });
+}
+
+pub fn info(node: &str, message: &str) {
+    log(Level::Info, node, message);
+}
+
+pub fn warn(node: &str, message: &str) {
+    log(Level::Warn, node, message);
+}
+
+pub fn error(node: &str, message: &str) {
+    log(Level::Error, node, message);
+}
@@ -0,0 +1,230 @@
+use std::fmt;
+
+use crate::vfs;
+
+/// The extension of the Python programs that are run as executables
+pub const EXTENSION: &str = "py";
+// files that belong to a package but can not be run
+const EXCLUDED_FILES: [&str; 2] = ["__init__.py", "setup.py"];
+// marks a directory as a Python module that is imported by the executables
+// next to it, rather than a package of its own
+const MODULE_MARKER: &str = "__init__.py";
+
+/// A directory of the project that contains Python programs, each of which is
+/// an executable named after its file without the extension, e.g., the
+/// package `velocity_control` with the executable `run` is found at
+/// `velocity_control/run.py`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Package {
+    pub name: String,
+    /// The absolute path of the directory
+    pub path: String,
+    /// The names of the executables, sorted
+    pub executables: Vec<String>,
+}
+
+impl Package {
+    /// The absolute path of the program that runs the executable
+    pub fn program(&self, executable: &str) -> String {
+        format!("{}/{executable}.{EXTENSION}", self.path)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    PackageNotFound {
+        package: String,
+        available: Vec<String>,
+    },
+    AmbiguousPackage {
+        package: String,
+        paths: Vec<String>,
+    },
+    ExecutableNotFound {
+        package: String,
+        executable: String,
+        available: Vec<String>,
+    },
+}
+
+// joins the names as `a, b and c`
+fn list(names: &[String]) -> String {
+    match names {
+        [] => String::new(),
+        [name] => format!("`{name}`"),
+        [names @ .., last] => format!("{} and `{last}`", names.iter()
+            .map(|name| format!("`{name}`"))
+            .collect::<Vec<_>>()
+            .join(", ")),
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::PackageNotFound { package, available } if available.is_empty() =>
+                write!(f, "package `{package}` not found, the project does not contain any packages"),
+            Error::PackageNotFound { package, available } =>
+                write!(f, "package `{package}` not found, the project contains {}", list(available)),
+            Error::AmbiguousPackage { package, paths } =>
+                write!(f, "package `{package}` is ambiguous, it exists at {}", list(paths)),
+            Error::ExecutableNotFound { package, executable, available } if available.is_empty() =>
+                write!(f, "package `{package}` has no executable `{executable}`, add {executable}.{EXTENSION} to it"),
+            Error::ExecutableNotFound { package, executable, available } =>
+                write!(f, "package `{package}` has no executable `{executable}`, its executables are {}", list(available)),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Finds the packages anywhere below the root of the project, the root itself
+/// holds the launch file and is not a package
+pub fn scan(project: &vfs::Directory) -> Vec<Package> {
+    fn visit(directory: &vfs::Directory, path: &str, packages: &mut Vec<Package>) {
+        for directory in directory.directories.lock_ref().iter() {
+            let name = directory.name.get_cloned();
+            let path = format!("{path}/{name}");
+            let files = directory.files.lock_ref()
+                .iter()
+                .map(|file| file.name.get_cloned())
+                .collect::<Vec<_>>();
+            if files.iter().any(|file| file == MODULE_MARKER) {
+                continue;
+            }
+            let mut executables = files.iter()
+                .filter(|file| !EXCLUDED_FILES.contains(&file.as_str()))
+                .filter_map(|file| file.strip_suffix(&format!(".{EXTENSION}")))
+                .filter(|executable| !executable.is_empty())
+                .map(str::to_owned)
+                .collect::<Vec<_>>();
+            if !executables.is_empty() {
+                executables.sort();
+                packages.push(Package { name, path: path.clone(), executables });
+            }
+            visit(directory, &path, packages);
+        }
+    }
+    let mut packages = Vec::new();
+    visit(project, &format!("/{}", project.name.lock_ref()), &mut packages);
+    packages.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.path.cmp(&b.path)));
+    packages
+}
+
+/// Looks up the executable `executable` of the package `package` and returns
+/// the absolute path of its program
+pub fn resolve(packages: &[Package], package: &str, executable: &str) -> Result<String, Error> {
+    let matches = packages.iter()
+        .filter(|candidate| candidate.name == package)
+        .collect::<Vec<_>>();
+    let found = match matches[..] {
+        [] => {
+            let mut available = packages.iter()
+                .map(|package| package.name.clone())
+                .collect::<Vec<_>>();
+            available.dedup();
+            return Err(Error::PackageNotFound { package: package.to_owned(), available });
+        }
+        [found] => found,
+        _ => return Err(Error::AmbiguousPackage {
+            package: package.to_owned(),
+            paths: matches.iter().map(|package| package.path.clone()).collect(),
+        }),
+    };
+    match found.executables.iter().any(|candidate| candidate == executable) {
+        true => Ok(found.program(executable)),
+        false => Err(Error::ExecutableNotFound {
+            package: package.to_owned(),
+            executable: executable.to_owned(),
+            available: found.executables.clone(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+
+    fn project(files: &[&str]) -> Rc<vfs::Directory> {
+        let project = Rc::new(vfs::Directory::new("project", 0o775));
+        for path in files {
+            let mut directory = String::from("/project");
+            let (directories, _) = path.rsplit_once('/').unwrap();
+            for name in directories.split('/') {
+                directory = format!("{directory}/{name}");
+                if project.lookup(&directory).is_err() {
+                    project.create_directory(&directory, 0o775).unwrap();
+                }
+            }
+            project.create_file(&format!("/project/{path}"), 0o664, vec![]).unwrap();
+        }
+        project
+    }
+
+    #[test]
+    fn scan_packages() {
+        let project = project(&[
+            "velocity_control/turn.py",
+            "velocity_control/run.py",
+            "velocity_control/setup.py",
+            "velocity_control/velocity_control/__init__.py",
+            "velocity_control/velocity_control/helpers.py",
+            "velocity_control/velocity_control/nested/main.py",
+            "src/wander/main.py",
+            "src/wander/README.md",
+            "src/empty/__init__.py",
+        ]);
+        assert_eq!(scan(&project), vec![
+            Package {
+                name: "velocity_control".to_owned(),
+                path: "/project/velocity_control".to_owned(),
+                executables: vec!["run".to_owned(), "turn".to_owned()],
+            },
+            Package {
+                name: "wander".to_owned(),
+                path: "/project/src/wander".to_owned(),
+                executables: vec!["main".to_owned()],
+            },
+        ]);
+    }
+
+    #[test]
+    fn resolve_executables() {
+        let packages = scan(&project(&["velocity_control/run.py", "velocity_control/setup.py"]));
+        assert_eq!(resolve(&packages, "velocity_control", "run").unwrap(), "/project/velocity_control/run.py");
+        assert_eq!(resolve(&packages, "velocity_control", "setup").unwrap_err().to_string(),
+            "package `velocity_control` has no executable `setup`, its executables are `run`");
+    }
+
+    #[test]
+    fn ambiguous_packages() {
+        let packages = scan(&project(&["b/wander/main.py", "a/wander/main.py", "wander/main.py", "avoid/main.py"]));
+        assert_eq!(packages.iter().map(|package| package.path.as_str()).collect::<Vec<_>>(), [
+            "/project/avoid",
+            "/project/a/wander",
+            "/project/b/wander",
+            "/project/wander",
+        ]);
+        assert_eq!(resolve(&packages, "wander", "main").unwrap_err().to_string(),
+            "package `wander` is ambiguous, it exists at `/project/a/wander`, `/project/b/wander` and `/project/wander`");
+        assert_eq!(resolve(&packages, "follow", "main").unwrap_err().to_string(),
+            "package `follow` not found, the project contains `avoid` and `wander`");
+    }
+
+    #[test]
+    fn error_messages() {
+        assert_eq!(resolve(&[], "wander", "main").unwrap_err().to_string(),
+            "package `wander` not found, the project does not contain any packages");
+        let packages = scan(&project(&["wander/main.py", "wander/turn.py", "wander/run.py"]));
+        assert_eq!(resolve(&packages, "wander", "stop").unwrap_err().to_string(),
+            "package `wander` has no executable `stop`, its executables are `main`, `run` and `turn`");
+        let error = Error::ExecutableNotFound {
+            package: "wander".to_owned(),
+            executable: "main".to_owned(),
+            available: vec![],
+        };
+        assert_eq!(error.to_string(), "package `wander` has no executable `main`, add main.py to it");
+    }
+}
@@ -0,0 +1,136 @@
+use std::rc::Rc;
+
+use dominator::clone;
+use futures::StreamExt;
+use gloo_timers::future::TimeoutFuture;
+use js_sys::{Array, Object, Promise, Reflect, Uint8Array};
+use wasm_bindgen::{prelude::*, JsCast};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{IdbDatabase, IdbRequest, IdbTransactionMode, IdbVersionChangeEvent};
+
+use crate::vfs;
+
+const DATABASE_NAME: &str = "ide";
+const DATABASE_VERSION: u32 = 1;
+const STORE_NAME: &str = "projects";
+const PROJECT_KEY: &str = "project";
+// increment this when the layout of the stored value changes
+const FORMAT_VERSION: u32 = 1;
+// wait for edits to settle before writing the project to storage
+const AUTOSAVE_DELAY_MS: u32 = 1000;
+
+async fn request(request: &IdbRequest) -> Result<JsValue, JsValue> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(&resolve));
+        request.set_onerror(Some(&reject));
+    });
+    JsFuture::from(promise).await?;
+    request.result()
+}
+
+async fn open() -> Result<IdbDatabase, JsValue> {
+    let factory = web_sys::window()
+        .ok_or("window is not available")?
+        .indexed_db()?
+        .ok_or("IndexedDB is not available")?;
+    let open_request = factory.open_with_u32(DATABASE_NAME, DATABASE_VERSION)?;
+    let on_upgrade_needed = Closure::<dyn FnMut(_)>::new(clone!(open_request => move |_: IdbVersionChangeEvent| {
+        let database = open_request.result().unwrap().unchecked_into::<IdbDatabase>();
+        database.create_object_store(STORE_NAME).unwrap();
+    }));
+    open_request.set_onupgradeneeded(Some(on_upgrade_needed.as_ref().unchecked_ref()));
+    let database = request(&open_request).await;
+    open_request.set_onupgradeneeded(None);
+    Ok(database?.unchecked_into())
+}
+
+fn encode(entries: Vec<vfs::Entry>) -> Result<Object, JsValue> {
+    let encoded_entries = entries.into_iter()
+        .map(|entry| {
+            let encoded_entry = Object::new();
+            Reflect::set(&encoded_entry, &"path".into(), &entry.path.into())?;
+            Reflect::set(&encoded_entry, &"mode".into(), &entry.mode.into())?;
+            if let Some(data) = entry.data {
+                Reflect::set(&encoded_entry, &"data".into(), &Uint8Array::from(&data[..]))?;
+            }
+            Ok(encoded_entry)
+        })
+        .collect::<Result<Array, JsValue>>()?;
+    let value = Object::new();
+    Reflect::set(&value, &"version".into(), &FORMAT_VERSION.into())?;
+    Reflect::set(&value, &"entries".into(), &encoded_entries)?;
+    Ok(value)
+}
+
+fn decode(value: &JsValue) -> Result<Vec<vfs::Entry>, JsValue> {
+    let version = Reflect::get(value, &"version".into())?.as_f64();
+    if version != Some(FORMAT_VERSION.into()) {
+        return Err(format!("unsupported format version: {version:?}").into());
+    }
+    Reflect::get(value, &"entries".into())?
+        .dyn_into::<Array>()?
+        .iter()
+        .map(|entry| {
+            let data = Reflect::get(&entry, &"data".into())?;
+            Ok(vfs::Entry {
+                path: Reflect::get(&entry, &"path".into())?
+                    .as_string()
+                    .ok_or("entry is missing a path")?,
+                mode: Reflect::get(&entry, &"mode".into())?
+                    .as_f64()
+                    .ok_or("entry is missing a mode")? as u32,
+                data: match data.is_undefined() {
+                    true => None,
+                    false => Some(data.dyn_into::<Uint8Array>()?.to_vec()),
+                }
+            })
+        })
+        .collect()
+}
+
+/// Writes the project to IndexedDB, replacing any previously saved project
+pub async fn save(project: &vfs::Directory) -> Result<(), JsValue> {
+    // take the snapshot before yielding so that it is consistent
+    let value = encode(project.entries())?;
+    let database = open().await?;
+    let transaction = database.transaction_with_str_and_mode(STORE_NAME, IdbTransactionMode::Readwrite)?;
+    let store = transaction.object_store(STORE_NAME)?;
+    let result = request(&store.put_with_key(&value, &PROJECT_KEY.into())?).await;
+    database.close();
+    result.map(|_| ())
+}
+
+/// Reads the project from IndexedDB, returning `None` if nothing has been saved yet
+pub async fn load() -> Result<Option<Rc<vfs::Directory>>, JsValue> {
+    let database = open().await?;
+    let transaction = database.transaction_with_str(STORE_NAME)?;
+    let store = transaction.object_store(STORE_NAME)?;
+    let value = request(&store.get(&PROJECT_KEY.into())?).await;
+    database.close();
+    let value = value?;
+    if value.is_undefined() {
+        return Ok(None);
+    }
+    vfs::Directory::from_entries(decode(&value)?)
+        .map(Some)
+        .map_err(|error| error.to_string().into())
+}
+
+/// Saves the project whenever it changes, this future runs forever
+pub async fn autosave() {
+    let mut events = vfs::subscribe();
+    while events.next().await.is_some() {
+        TimeoutFuture::new(AUTOSAVE_DELAY_MS).await;
+        // coalesce the changes that were made in the meantime
+        while let Ok(Some(_)) = events.try_next() {}
+        let project = crate::PROJECT.with(|project| Rc::clone(project));
+        if let Err(error) = save(&project).await {
+            tracing::error!("could not save project: {error:?}");
+        }
+    }
+}
+
+/// Replaces the project with the default template
+pub fn reset() {
+    crate::PROJECT.with(|project| project.replace(&crate::default_project()));
+}
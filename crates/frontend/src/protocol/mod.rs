@@ -0,0 +1,390 @@
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{bus::{Message, ParameterValue}, log::Level, vfs};
+
+pub mod simulation;
+
+/// The version of the protocol, which the worker checks before it starts the
+/// program. Increment this whenever a change breaks compatibility with
+/// python_worker.js or the rclpy shim.
+pub const VERSION: u32 = 2;
+
+/// A file that is copied into the file system of a worker, the data is
+/// encoded as base64 so that it can be sent as JSON
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct File {
+    pub path: String,
+    pub mode: u32,
+    #[serde(with = "base64")]
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Directory {
+    pub path: String,
+    pub mode: u32,
+}
+
+/// A snapshot of the project with absolute paths, the directories are listed
+/// before their contents so that empty directories are created as well
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Project {
+    pub directories: Vec<Directory>,
+    pub files: Vec<File>,
+}
+
+impl Project {
+    /// Flattens the project, since its tree of shared and mutable nodes can not
+    /// be sent to a worker as it is
+    pub fn snapshot(project: &vfs::Directory) -> Project {
+        let mut snapshot = Project::default();
+        snapshot.add(project, "");
+        snapshot
+    }
+
+    // adds the directory inside of `parent_path` along with its contents
+    fn add(&mut self, directory: &vfs::Directory, parent_path: &str) {
+        let path = format!("{parent_path}/{}", directory.name.lock_ref());
+        self.directories.push(Directory {
+            path: path.clone(),
+            mode: directory.mode.get(),
+        });
+        self.files.extend(directory.files.lock_ref()
+            .iter()
+            .map(|file| File {
+                path: format!("{path}/{}", file.name.lock_ref()),
+                mode: file.mode.get(),
+                data: file.data.lock_ref().clone(),
+            }));
+        for directory in directory.directories.lock_ref().iter() {
+            self.add(directory, &path);
+        }
+    }
+}
+
+/// A change to the project while a program is running, which the worker
+/// applies to its copy of the project
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Change {
+    /// Creates the directory unless it exists
+    Directory(Directory),
+    /// Creates or replaces the file along with any missing parent directories
+    File(File),
+    /// Removes the file or directory, if it exists
+    Removed {
+        path: String,
+    },
+}
+
+impl Change {
+    /// The changes that bring a copy of the project up to date with `event`.
+    /// The project is looked up when the event is handled rather than when it
+    /// was emitted, so a new or renamed directory is sent with its contents,
+    /// and a node that is gone by then is not sent at all, since the event
+    /// that removed it follows.
+    pub fn from_event(project: &Rc<vfs::Directory>, event: &vfs::Event) -> Vec<Change> {
+        let mut changes = match &event.kind {
+            vfs::EventKind::Renamed { from } => vec![Change::Removed { path: from.clone() }],
+            vfs::EventKind::Removed => return vec![Change::Removed { path: event.path.clone() }],
+            vfs::EventKind::Created | vfs::EventKind::Modified => Vec::new(),
+        };
+        match project.lookup(&event.path) {
+            Ok(vfs::Node::File(file)) => changes.push(Change::File(File {
+                path: event.path.clone(),
+                mode: file.mode.get(),
+                data: file.data.get_cloned(),
+            })),
+            Ok(vfs::Node::Directory(directory)) => {
+                let mut snapshot = Project::default();
+                snapshot.add(&directory, &event.path[..event.path.rfind('/').unwrap_or(0)]);
+                changes.extend(snapshot.directories.into_iter().map(Change::Directory));
+                changes.extend(snapshot.files.into_iter().map(Change::File));
+            }
+            Err(_) => {}
+        }
+        changes
+    }
+}
+
+/// The first message to a worker, which is sent along with the mailbox
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Start {
+    pub version: u32,
+    /// The absolute path of the program to run
+    pub program: String,
+    /// The modules that stand in for rclpy and the message packages, their
+    /// paths are relative to a directory on the module search path
+    pub shim: Vec<File>,
+    pub project: Project,
+}
+
+/// Sent from the UI thread to a worker through its mailbox
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    /// Replies to [Request::CreatePublisher] and [Request::CreateSubscription]
+    Created {
+        request: u64,
+        id: u64,
+    },
+    /// Replies to [Request::DeclareParameter] with the value that takes effect
+    Declared {
+        request: u64,
+        value: ParameterValue,
+    },
+    /// Replies to any request that could not be carried out
+    Failed {
+        request: u64,
+        error: String,
+    },
+    Message {
+        subscription: u64,
+        message: Message,
+    },
+    /// The simulated time in seconds, which drives the timers of the nodes
+    Clock {
+        time: f64,
+    },
+    /// A parameter was changed from the outside
+    Parameter {
+        node: String,
+        name: String,
+        value: ParameterValue,
+    },
+    /// The project was changed, modules that were imported already are not
+    /// reloaded
+    Sync {
+        change: Change,
+    },
+    /// Asks the program to shut down
+    Stop,
+}
+
+/// Sent from a worker to the UI thread with `postMessage`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    CreatePublisher {
+        request: u64,
+        node: String,
+        namespace: String,
+        topic: String,
+        message_type: String,
+    },
+    CreateSubscription {
+        request: u64,
+        node: String,
+        namespace: String,
+        topic: String,
+        message_type: String,
+        depth: usize,
+    },
+    /// Declares a parameter, `value` is the default, if any
+    DeclareParameter {
+        request: u64,
+        node: String,
+        namespace: String,
+        name: String,
+        value: Option<ParameterValue>,
+    },
+    Publish {
+        publisher: u64,
+        message: Message,
+    },
+    /// Removes a publisher or subscription
+    Destroy {
+        id: u64,
+    },
+    Log {
+        level: Level,
+        node: String,
+        message: String,
+    },
+    /// The program returned
+    Exit,
+    /// The program raised an exception or the worker failed to start it
+    Error {
+        message: String,
+    },
+}
+
+impl Request {
+    /// The id that the reply to this request has to carry, if it expects one
+    pub fn id(&self) -> Option<u64> {
+        match self {
+            Request::CreatePublisher { request, .. } |
+            Request::CreateSubscription { request, .. } |
+            Request::DeclareParameter { request, .. } => Some(*request),
+            _ => None,
+        }
+    }
+}
+
+// the standard alphabet with padding, which `atob` in the worker decodes
+mod base64 {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    pub fn encode(data: &[u8]) -> String {
+        let mut text = String::with_capacity(data.len().div_ceil(3) * 4);
+        for chunk in data.chunks(3) {
+            let bits = (0..3).fold(0, |bits, index| bits << 8 | u32::from(chunk.get(index).copied().unwrap_or(0)));
+            for index in 0..4 {
+                match index <= chunk.len() {
+                    true => text.push(char::from(ALPHABET[(bits >> (18 - 6 * index) & 63) as usize])),
+                    false => text.push('='),
+                }
+            }
+        }
+        text
+    }
+
+    pub fn decode(text: &str) -> Option<Vec<u8>> {
+        let mut data = Vec::with_capacity(text.len() / 4 * 3);
+        let (mut bits, mut count) = (0_u32, 0);
+        for character in text.trim_end_matches('=').bytes() {
+            let value = ALPHABET.iter().position(|candidate| *candidate == character)?;
+            // only the bits that have not been written yet are kept
+            bits = (bits << 6 | value as u32) & 0xffff;
+            count += 6;
+            if count >= 8 {
+                count -= 8;
+                data.push((bits >> count) as u8);
+            }
+        }
+        Some(data)
+    }
+
+    pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&encode(data))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        decode(&text).ok_or_else(|| de::Error::custom("invalid base64"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{de::DeserializeOwned, Serialize};
+
+    use super::*;
+    use crate::bus::message::Velocity;
+
+    pub(super) fn round_trip<T: Serialize + DeserializeOwned + PartialEq + std::fmt::Debug>(value: T) {
+        let text = serde_json::to_string(&value).unwrap();
+        assert_eq!(serde_json::from_str::<T>(&text).unwrap(), value, "{text}");
+    }
+
+    #[test]
+    fn base64_round_trip() {
+        let data = (0..=255).collect::<Vec<u8>>();
+        for length in 0..=data.len() {
+            assert_eq!(base64::decode(&base64::encode(&data[..length])).as_deref(), Some(&data[..length]));
+        }
+        assert_eq!(base64::encode(b"hello world"), "aGVsbG8gd29ybGQ=");
+        assert_eq!(base64::decode("aGVsbG8gd29ybGQ=").unwrap(), b"hello world");
+        assert_eq!(base64::decode("a*=="), None);
+    }
+
+    #[test]
+    fn start_round_trip() {
+        let file = |path: &str, data: &[u8]| File { path: path.to_owned(), mode: crate::DEFAULT_FILE_MODE, data: data.to_vec() };
+        let start = Start {
+            version: VERSION,
+            program: "/project/velocity_control/run.py".to_owned(),
+            shim: vec![file("rclpy/__init__.py", b"")],
+            project: Project {
+                directories: vec![Directory { path: "/project".to_owned(), mode: crate::DEFAULT_DIRECTORY_MODE }],
+                files: vec![file("/project/data.bin", &[0, 1, 254, 255])],
+            },
+        };
+        let value = serde_json::to_value(&start).unwrap();
+        assert_eq!(value["version"], VERSION);
+        assert_eq!(value["project"]["files"][0]["data"], "AAH+/w==");
+        round_trip(start);
+    }
+
+    #[test]
+    fn worker_version() {
+        let worker = include_str!("../../../../static/python_worker.js");
+        assert!(worker.contains(&format!("const PROTOCOL_VERSION = {VERSION};")),
+            "python_worker.js does not speak version {VERSION} of the protocol");
+    }
+
+    #[test]
+    fn event_round_trip() {
+        let events = [
+            Event::Created { request: 1, id: 2 },
+            Event::Declared { request: 3, value: ParameterValue::Integer(4) },
+            Event::Failed { request: 5, error: "unknown message type".to_owned() },
+            Event::Message { subscription: 6, message: Velocity { left: 1.0, right: -1.0 }.into() },
+            Event::Clock { time: 0.5 },
+            Event::Parameter { node: "/velocity_control".to_owned(), name: "forward_speed".to_owned(), value: ParameterValue::Double(2.0) },
+            Event::Sync { change: Change::Directory(Directory { path: "/project/a".to_owned(), mode: crate::DEFAULT_DIRECTORY_MODE }) },
+            Event::Sync { change: Change::File(File { path: "/project/a/b.py".to_owned(), mode: crate::DEFAULT_FILE_MODE, data: b"pass".to_vec() }) },
+            Event::Sync { change: Change::Removed { path: "/project/a".to_owned() } },
+            Event::Stop,
+        ];
+        for event in events {
+            round_trip(event);
+        }
+        assert_eq!(serde_json::to_string(&Event::Stop).unwrap(), r#"{"type":"stop"}"#);
+    }
+
+    #[test]
+    fn request_round_trip() {
+        // as sent by the rclpy shim
+        let requests = [
+            (r#"{"type": "create_subscription", "request": 1, "node": "n", "namespace": "/", "topic": "odom",
+                "message_type": "example_interfaces/msg/Odometry", "depth": 10}"#, Some(1)),
+            (r#"{"type": "declare_parameter", "request": 2, "node": "n", "namespace": "/", "name": "speed",
+                "value": {"type": "double", "value": 1.5}}"#, Some(2)),
+            (r#"{"type": "declare_parameter", "request": 3, "node": "n", "namespace": "/", "name": "speed", "value": null}"#, Some(3)),
+            (r#"{"type": "publish", "publisher": 0,
+                "message": {"type": "example_interfaces/msg/Velocity", "data": {"left": 5.0, "right": -5.0}}}"#, None),
+            (r#"{"type": "destroy", "id": 0}"#, None),
+            (r#"{"type": "log", "level": "WARN", "node": "n", "message": "slow"}"#, None),
+            (r#"{"type": "exit"}"#, None),
+            (r#"{"type": "error", "message": "NameError"}"#, None),
+        ];
+        for (text, id) in requests {
+            let request = serde_json::from_str::<Request>(text).unwrap();
+            assert_eq!(request.id(), id, "{text}");
+            round_trip(request);
+        }
+        assert!(serde_json::from_str::<Request>(r#"{"type": "launch"}"#).is_err());
+    }
+
+    #[test]
+    fn changes_from_events() {
+        let project = Rc::new(vfs::Directory::new("project", crate::DEFAULT_DIRECTORY_MODE));
+        project.create_directory("/project/a", crate::DEFAULT_DIRECTORY_MODE).unwrap();
+        project.create_directory("/project/a/b", crate::DEFAULT_DIRECTORY_MODE).unwrap();
+        project.create_file("/project/a/b/c.py", crate::DEFAULT_FILE_MODE, b"pass".to_vec()).unwrap();
+        let event = |path: &str, kind| vfs::Event { path: path.to_owned(), kind };
+        let directory = |path: &str| Change::Directory(Directory { path: path.to_owned(), mode: crate::DEFAULT_DIRECTORY_MODE });
+        let file = Change::File(File { path: "/project/a/b/c.py".to_owned(), mode: crate::DEFAULT_FILE_MODE, data: b"pass".to_vec() });
+
+        assert_eq!(Change::from_event(&project, &event("/project/a/b/c.py", vfs::EventKind::Modified)), [file.clone()]);
+        assert_eq!(Change::from_event(&project, &event("/project/a", vfs::EventKind::Renamed { from: "/project/d".to_owned() })), [
+            Change::Removed { path: "/project/d".to_owned() },
+            directory("/project/a"),
+            directory("/project/a/b"),
+            file.clone(),
+        ]);
+        assert_eq!(Change::from_event(&project, &event("/project/a/b/c.py", vfs::EventKind::Removed)),
+            [Change::Removed { path: "/project/a/b/c.py".to_owned() }]);
+        assert_eq!(Change::from_event(&project, &event("/project/e.py", vfs::EventKind::Created)), []);
+
+        let value = serde_json::to_value(Event::Sync { change: file }).unwrap();
+        assert_eq!(value["type"], "sync");
+        assert_eq!(value["change"]["kind"], "file");
+        assert_eq!(value["change"]["data"], "cGFzcw==");
+    }
+}
@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Change, Project};
+use crate::{bus::Message, log::Level};
+
+/// Sent from the UI thread to the simulation worker with `postMessage`. The
+/// worker steps the world of the launch file at the root of the project, while
+/// the Python nodes keep talking to the UI thread, which forwards their
+/// messages.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Command {
+    Start {
+        version: u32,
+        project: Project,
+        /// The path of a bag to replay instead of running the nodes
+        replay: Option<String>,
+    },
+    Sync {
+        change: Change,
+    },
+    /// Advances the world by `count` steps
+    Step {
+        count: u32,
+    },
+    /// A message that a node published on a topic of the world
+    Publish {
+        topic: String,
+        message: Message,
+    },
+    Stop,
+}
+
+/// Sent from the simulation worker to the UI thread with `postMessage`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Update {
+    /// The world was loaded and waits for the first step
+    Started,
+    /// A message that the world published, e.g., a pose or a sensor reading
+    Message {
+        topic: String,
+        message: Message,
+    },
+    /// The simulated time in seconds after the last step that was asked for
+    Stepped {
+        time: f64,
+    },
+    Log {
+        level: Level,
+        node: String,
+        message: String,
+    },
+    /// The world could not be loaded or stepped, it is stopped
+    Error {
+        message: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bus::message::Velocity, protocol::{tests::round_trip, File, VERSION}};
+
+    #[test]
+    fn command_round_trip() {
+        let file = File { path: "/project/launch.xml".to_owned(), mode: crate::DEFAULT_FILE_MODE, data: b"<launch />".to_vec() };
+        let commands = [
+            Command::Start { version: VERSION, project: Project { directories: vec![], files: vec![file.clone()] }, replay: None },
+            Command::Start { version: VERSION, project: Project::default(), replay: Some("/project/run.bag".to_owned()) },
+            Command::Sync { change: Change::File(file) },
+            Command::Step { count: 5 },
+            Command::Publish { topic: "/velocity".to_owned(), message: Velocity { left: 1.0, right: 1.0 }.into() },
+            Command::Stop,
+        ];
+        for command in commands {
+            round_trip(command);
+        }
+        assert_eq!(serde_json::to_string(&Command::Step { count: 1 }).unwrap(), r#"{"type":"step","count":1}"#);
+    }
+
+    #[test]
+    fn update_round_trip() {
+        let updates = [
+            Update::Started,
+            Update::Message { topic: "/velocity".to_owned(), message: Velocity { left: 0.5, right: -0.5 }.into() },
+            Update::Stepped { time: 0.02 },
+            Update::Log { level: Level::Warn, node: "simulator".to_owned(), message: "slow".to_owned() },
+            Update::Error { message: "launch.xml:1:1: the root element must be <launch>".to_owned() },
+        ];
+        for update in updates {
+            round_trip(update);
+        }
+    }
+}
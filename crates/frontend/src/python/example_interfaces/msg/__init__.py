@@ -0,0 +1,61 @@
+class Velocity:
+    """The angular velocities of the left and right wheels in rad/s"""
+
+    _type = 'example_interfaces/msg/Velocity'
+    __slots__ = ('left', 'right')
+
+    def __init__(self, left=0.0, right=0.0):
+        self.left = float(left)
+        self.right = float(right)
+
+    def __repr__(self):
+        return f'Velocity(left={self.left}, right={self.right})'
+
+
+class Contact:
+    """A robot started touching the boundary of the world or another model"""
+
+    _type = 'example_interfaces/msg/Contact'
+    __slots__ = ('model', 'other', 'x', 'z')
+
+    def __init__(self, model='', other='', x=0.0, z=0.0):
+        self.model = str(model)
+        self.other = str(other)
+        self.x = float(x)
+        self.z = float(z)
+
+    def __repr__(self):
+        return f'Contact(model={self.model!r}, other={self.other!r}, x={self.x}, z={self.z})'
+
+
+class Pose:
+    """A position on the ground plane in meters and a heading in radians"""
+
+    _type = 'example_interfaces/msg/Pose'
+    __slots__ = ('x', 'z', 'heading')
+
+    def __init__(self, x=0.0, z=0.0, heading=0.0):
+        self.x = float(x)
+        self.z = float(z)
+        self.heading = float(heading)
+
+    def __repr__(self):
+        return f'Pose(x={self.x}, z={self.z}, heading={self.heading})'
+
+
+class Odometry:
+    """The pose estimated from the wheels, the linear velocity in m/s and the angular velocity in rad/s"""
+
+    _type = 'example_interfaces/msg/Odometry'
+    __slots__ = ('x', 'z', 'heading', 'linear', 'angular')
+
+    def __init__(self, x=0.0, z=0.0, heading=0.0, linear=0.0, angular=0.0):
+        self.x = float(x)
+        self.z = float(z)
+        self.heading = float(heading)
+        self.linear = float(linear)
+        self.angular = float(angular)
+
+    def __repr__(self):
+        return (f'Odometry(x={self.x}, z={self.z}, heading={self.heading}, '
+            f'linear={self.linear}, angular={self.angular})')
@@ -0,0 +1,371 @@
+use std::{cell::RefCell, collections::{BTreeMap, VecDeque}, rc::Rc};
+
+use dominator::clone;
+use futures::channel::mpsc;
+use js_sys::{Atomics, Int32Array, Object, Reflect, SharedArrayBuffer, Uint8Array};
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::{ErrorEvent, MessageEvent, Worker};
+
+use crate::{
+    bus::{self, Bus, MessageType, ParameterValue},
+    launch,
+    log,
+    protocol::{self, Change, Event, Project, Request, Start},
+    vfs,
+};
+
+const WORKER_URL: &str = "python_worker.js";
+// the mailbox starts with the read and write offsets into the ring buffer that follows them
+const READ_INDEX: u32 = 0;
+const WRITE_INDEX: u32 = 1;
+const HEADER_BYTES: u32 = 8;
+const MAILBOX_CAPACITY: u32 = 1 << 20;
+// the time that a program gets to shut down after it was asked to stop
+const STOP_TIMEOUT_MS: u32 = 1000;
+
+/// Modules that stand in for rclpy and the message packages, the paths are
+/// relative to a directory that is added to the module search path
+const SHIM: [(&str, &str); 5] = [
+    ("rclpy/__init__.py", include_str!("rclpy/__init__.py")),
+    ("rclpy/node.py", include_str!("rclpy/node.py")),
+    ("rclpy/parameter.py", include_str!("rclpy/parameter.py")),
+    ("example_interfaces/msg/__init__.py", include_str!("example_interfaces/msg/__init__.py")),
+    ("sensor_msgs/msg/__init__.py", include_str!("sensor_msgs/msg/__init__.py")),
+];
+
+/// A ring buffer in shared memory that carries events to the worker. The
+/// worker blocks on it while Python is running, since it can not receive
+/// events from `postMessage` until the program returns.
+#[derive(Clone)]
+struct Mailbox {
+    buffer: SharedArrayBuffer,
+    header: Int32Array,
+    data: Uint8Array,
+}
+
+impl Mailbox {
+    fn new() -> Mailbox {
+        let buffer = SharedArrayBuffer::new(HEADER_BYTES + MAILBOX_CAPACITY);
+        Mailbox {
+            header: Int32Array::new_with_byte_offset_and_length(&buffer, 0, 2),
+            data: Uint8Array::new_with_byte_offset(&buffer, HEADER_BYTES),
+            buffer,
+        }
+    }
+
+    // writes the event as JSON prefixed with its length in bytes
+    fn send(&self, event: &Event) -> Result<(), JsValue> {
+        let text = serde_json::to_string(event)
+            .map_err(|error| JsValue::from_str(&error.to_string()))?;
+        let frame = (text.len() as u32).to_le_bytes()
+            .into_iter()
+            .chain(text.bytes())
+            .collect::<Vec<_>>();
+        let read = Atomics::load(&self.header, READ_INDEX)? as u32;
+        let write = Atomics::load(&self.header, WRITE_INDEX)? as u32;
+        let used = (write + MAILBOX_CAPACITY - read) % MAILBOX_CAPACITY;
+        // one byte stays free so that a full mailbox can be told apart from an empty one
+        if used as usize + frame.len() >= MAILBOX_CAPACITY as usize {
+            return Err("the mailbox is full".into());
+        }
+        let split = frame.len().min((MAILBOX_CAPACITY - write) as usize);
+        self.data.subarray(write, write + split as u32).copy_from(&frame[..split]);
+        self.data.subarray(0, (frame.len() - split) as u32).copy_from(&frame[split..]);
+        Atomics::store(&self.header, WRITE_INDEX, ((write as usize + frame.len()) % MAILBOX_CAPACITY as usize) as i32)?;
+        Atomics::notify(&self.header, WRITE_INDEX)?;
+        Ok(())
+    }
+}
+
+// the fully qualified name of a node
+fn node_name(namespace: &str, node: &str) -> String {
+    match namespace {
+        "/" => format!("/{node}"),
+        _ => format!("{namespace}/{node}"),
+    }
+}
+
+// the publishers and subscriptions that the program created, by their ids,
+// and the parameters it declared along with the values it was last sent
+#[derive(Default)]
+struct State {
+    // set once the program exited or failed, it no longer receives events
+    finished: bool,
+    next_id: u64,
+    publishers: BTreeMap<u64, bus::Publisher>,
+    subscriptions: BTreeMap<u64, bus::Subscription>,
+    // the values from the launch file, which take precedence over the defaults
+    overrides: Vec<launch::Parameter>,
+    parameters: Vec<(bus::Parameter, ParameterValue)>,
+    // the changes to the project that did not fit into the mailbox yet
+    changes: VecDeque<Change>,
+}
+
+impl State {
+    // creates a subscription with the given depth, or a publisher without one
+    fn create_endpoint(&mut self, bus: &Rc<Bus>, node: &str, namespace: &str, topic: &str, message_type: &str, depth: Option<usize>) -> Result<u64, String> {
+        let node = node_name(namespace, node);
+        let topic = bus::resolve_name(namespace, topic)
+            .map_err(|error| error.to_string())?;
+        let message_type = MessageType::from_name(message_type)
+            .ok_or_else(|| format!("unknown message type `{message_type}`"))?;
+        let id = self.next_id;
+        match depth {
+            Some(depth) => {
+                let subscription = bus.create_subscription(&node, &topic, message_type, depth)
+                    .map_err(|error| error.to_string())?;
+                self.subscriptions.insert(id, subscription);
+            }
+            None => {
+                let publisher = bus.create_publisher(&node, &topic, message_type)
+                    .map_err(|error| error.to_string())?;
+                self.publishers.insert(id, publisher);
+            }
+        }
+        self.next_id += 1;
+        Ok(id)
+    }
+
+    fn declare_parameter(&mut self, bus: &Rc<Bus>, node: &str, name: &str, default: Option<ParameterValue>) -> Result<ParameterValue, String> {
+        let value = match (self.overrides.iter().find(|parameter| parameter.name == name), default) {
+            (Some(parameter), Some(default)) => parameter.value.clone()
+                .coerce_to(&default)
+                .ok_or_else(|| format!("the parameter {name} is of type {} in the launch file but declared as {}",
+                    parameter.value.type_name(), default.type_name()))?,
+            (Some(parameter), None) => parameter.value.clone(),
+            (None, Some(default)) => default,
+            (None, None) => return Err(format!("the parameter {name} needs a default value or a value in the launch file")),
+        };
+        let parameter = bus.declare_parameter(node, name, value.clone())
+            .map_err(|error| error.to_string())?;
+        self.parameters.push((parameter, value.clone()));
+        Ok(value)
+    }
+
+    // the indices and values of the parameters that were changed from the
+    // outside since they were last sent
+    fn changed_parameters(&self) -> Vec<(usize, ParameterValue)> {
+        self.parameters.iter()
+            .enumerate()
+            .filter_map(|(index, (parameter, sent))| parameter.value()
+                .filter(|value| value != sent)
+                .map(|value| (index, value)))
+            .collect()
+    }
+
+    // the nodes of a program that finished are gone, so are their endpoints and parameters
+    fn finish(&mut self) {
+        self.finished = true;
+        self.publishers.clear();
+        self.subscriptions.clear();
+        self.parameters.clear();
+        self.changes.clear();
+    }
+
+    // carries out a request and returns the reply, if it expects one
+    fn handle(&mut self, name: &str, bus: &Rc<Bus>, request: Request) -> Result<Option<Event>, String> {
+        let reply_to = request.id();
+        let result = match request {
+            Request::CreatePublisher { request: id, node, namespace, topic, message_type } =>
+                self.create_endpoint(bus, &node, &namespace, &topic, &message_type, None)
+                    .map(|endpoint| Event::Created { request: id, id: endpoint }),
+            Request::CreateSubscription { request: id, node, namespace, topic, message_type, depth } =>
+                self.create_endpoint(bus, &node, &namespace, &topic, &message_type, Some(depth))
+                    .map(|endpoint| Event::Created { request: id, id: endpoint }),
+            Request::DeclareParameter { request: id, node, namespace, name, value } =>
+                self.declare_parameter(bus, &node_name(&namespace, &node), &name, value)
+                    .map(|value| Event::Declared { request: id, value }),
+            Request::Publish { publisher, message } => {
+                let publisher = self.publishers.get(&publisher)
+                    .ok_or_else(|| format!("publisher {publisher} does not exist"))?;
+                publisher.publish(message).map_err(|error| error.to_string())?;
+                return Ok(None);
+            }
+            Request::Destroy { id } => {
+                self.publishers.remove(&id);
+                self.subscriptions.remove(&id);
+                return Ok(None);
+            }
+            Request::Log { level, node, message } => {
+                log::log(level, &node, &message);
+                return Ok(None);
+            }
+            Request::Exit => {
+                log::info(log::SIMULATOR, &format!("{name} exited"));
+                self.finish();
+                return Ok(None);
+            }
+            Request::Error { message } => {
+                log::error(log::SIMULATOR, &format!("{name} failed: {message}"));
+                self.finish();
+                return Ok(None);
+            }
+        };
+        // the program raises the error when it receives the reply
+        match (result, reply_to) {
+            (Ok(reply), _) => Ok(Some(reply)),
+            (Err(error), Some(request)) => Ok(Some(Event::Failed { request, error })),
+            (Err(error), None) => Err(error),
+        }
+    }
+}
+
+/// A Python program running in a web worker, its publishers and subscriptions
+/// live on the bus of the main thread and are removed when it is dropped
+pub struct Process {
+    name: String,
+    worker: Worker,
+    mailbox: Mailbox,
+    state: Rc<RefCell<State>>,
+    // the changes to the project since the snapshot in the start message
+    project_changes: RefCell<mpsc::UnboundedReceiver<vfs::Event>>,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+    _on_error: Closure<dyn FnMut(ErrorEvent)>,
+}
+
+impl Process {
+    /// Starts the program at the absolute path `program` after copying the
+    /// project into the file system of the worker, `parameters` override the
+    /// defaults of the parameters that its nodes declare
+    pub fn spawn(name: &str, program: &str, project: &Project, parameters: &[launch::Parameter]) -> Result<Process, JsValue> {
+        let name = name.to_owned();
+        let bus = crate::BUS.with(|bus| Rc::clone(bus));
+        let mailbox = Mailbox::new();
+        let state = Rc::new(RefCell::new(State {
+            overrides: parameters.to_vec(),
+            ..Default::default()
+        }));
+        let worker = Worker::new(WORKER_URL)?;
+        let on_message = Closure::<dyn FnMut(_)>::new(clone!(name, mailbox, state => move |event: MessageEvent| {
+            let request = event.data()
+                .as_string()
+                .ok_or_else(|| "expected a request as JSON".to_owned())
+                .and_then(|request| serde_json::from_str::<Request>(&request)
+                    .map_err(|error| format!("invalid request: {error}")));
+            let reply = request.and_then(|request| state.borrow_mut().handle(&name, &bus, request));
+            let sent = match reply {
+                Ok(Some(reply)) => mailbox.send(&reply).map_err(|error| format!("{error:?}")),
+                Ok(None) => Ok(()),
+                Err(error) => Err(error),
+            };
+            if let Err(error) = sent {
+                log::error(log::SIMULATOR, &format!("{name}: {error}"));
+            }
+        }));
+        let on_error = Closure::<dyn FnMut(_)>::new(clone!(name, state => move |event: ErrorEvent| {
+            log::error(log::SIMULATOR, &format!("{name}: {}", event.message()));
+            state.borrow_mut().finish();
+        }));
+        worker.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        worker.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+
+        let start = Start {
+            version: protocol::VERSION,
+            program: program.to_owned(),
+            shim: SHIM.iter()
+                .map(|(path, source)| protocol::File {
+                    path: (*path).to_owned(),
+                    mode: crate::DEFAULT_FILE_MODE,
+                    data: source.as_bytes().to_vec(),
+                })
+                .collect(),
+            project: project.clone(),
+        };
+        let start = serde_json::to_string(&start)
+            .map_err(|error| JsValue::from_str(&error.to_string()))?;
+        // the mailbox is shared memory, which can only be sent as it is
+        let message = Object::new();
+        Reflect::set(&message, &"mailbox".into(), &mailbox.buffer)?;
+        Reflect::set(&message, &"start".into(), &start.into())?;
+        worker.post_message(&message)?;
+
+        Ok(Process {
+            name,
+            worker,
+            mailbox,
+            state,
+            project_changes: RefCell::new(vfs::subscribe()),
+            _on_message: on_message,
+            _on_error: on_error,
+        })
+    }
+
+    // returns whether the event was queued
+    fn send(&self, event: &Event) -> bool {
+        let result = self.mailbox.send(event);
+        if let Err(error) = &result {
+            log::warn(log::SIMULATOR, &format!("{}: dropped an event: {error:?}", self.name));
+        }
+        result.is_ok()
+    }
+
+    /// Forwards the changes to the project, the parameters that were changed
+    /// and the messages that arrived for the subscriptions of the program,
+    /// followed by the current time in seconds, which drives its timers.
+    /// Nothing is sent once it has finished.
+    pub fn step(&self, time: f64) {
+        let mut state = self.state.borrow_mut();
+        if state.finished {
+            return;
+        }
+        while let Ok(Some(event)) = self.project_changes.borrow_mut().try_next() {
+            let changes = crate::PROJECT.with(|project| Change::from_event(project, &event));
+            state.changes.extend(changes);
+        }
+        // the changes are applied in order, so the rest waits for the one that did not fit
+        while let Some(change) = state.changes.front() {
+            if !self.send(&Event::Sync { change: change.clone() }) {
+                break;
+            }
+            state.changes.pop_front();
+        }
+        for (index, value) in state.changed_parameters() {
+            let (parameter, sent) = &mut state.parameters[index];
+            let event = Event::Parameter {
+                node: parameter.node().to_owned(),
+                name: parameter.name().to_owned(),
+                value: value.clone(),
+            };
+            // a change that could not be queued is sent again in the next step
+            if self.send(&event) {
+                *sent = value;
+            }
+        }
+        let messages = state.subscriptions.iter()
+            .flat_map(|(id, subscription)| subscription.drain()
+                .into_iter()
+                .map(|message| Event::Message {
+                    subscription: *id,
+                    message,
+                }));
+        for event in messages.chain([Event::Clock { time }]) {
+            self.send(&event);
+        }
+    }
+
+    /// Asks the program to shut down, which lets it clean up before the worker
+    /// is terminated. The parameters are removed right away so that the nodes
+    /// can be started again in the meantime.
+    pub fn stop(self) {
+        let finished = {
+            let mut state = self.state.borrow_mut();
+            state.parameters.clear();
+            state.finished
+        };
+        if !finished {
+            self.send(&Event::Stop);
+        }
+        wasm_bindgen_futures::spawn_local(async move {
+            gloo_timers::future::TimeoutFuture::new(STOP_TIMEOUT_MS).await;
+            drop(self);
+        });
+    }
+}
+
+impl Drop for Process {
+    fn drop(&mut self) {
+        self.worker.terminate();
+        self.worker.set_onmessage(None);
+        self.worker.set_onerror(None);
+    }
+}
@@ -0,0 +1,117 @@
+"""A subset of rclpy for running nodes inside of the browser
+
+Requests are sent to the simulator on the main thread as JSON and events, such
+as incoming messages and clock updates, are read from a mailbox in shared
+memory. Reading from the mailbox blocks, which lets spin() behave as usual.
+Both are defined by the protocol in src/protocol/mod.rs of the frontend crate.
+"""
+
+import base64
+import collections
+import json
+import os
+import shutil
+
+import _bridge
+
+
+class _Context:
+    def __init__(self):
+        self.nodes = []
+        self.time = 0.0
+        self.running = True
+        self._next_request = 0
+        # events that arrived while waiting for a reply
+        self._pending = collections.deque()
+
+    def send(self, **request):
+        _bridge.send(json.dumps(request))
+
+    def request(self, **request):
+        """Sends a request and waits for the simulator to reply"""
+        self._next_request += 1
+        request['request'] = self._next_request
+        self.send(**request)
+        while True:
+            event = self._receive(None)
+            if event['type'] in ('created', 'declared', 'failed') and event['request'] == request['request']:
+                if event['type'] == 'failed':
+                    raise RuntimeError(event['error'])
+                return event
+            self._pending.append(event)
+
+    def _receive(self, timeout_sec):
+        timeout_ms = None if timeout_sec is None else timeout_sec * 1000.0
+        event = _bridge.receive(timeout_ms)
+        return None if event is None else json.loads(event)
+
+    def spin_once(self, timeout_sec):
+        event = self._pending.popleft() if self._pending else self._receive(timeout_sec)
+        if event is None:
+            return
+        if event['type'] == 'clock':
+            self.time = event['time']
+            for node in list(self.nodes):
+                node._on_clock(self.time)
+        elif event['type'] == 'message':
+            for node in list(self.nodes):
+                node._on_message(event['subscription'], event['message']['data'])
+        elif event['type'] == 'parameter':
+            for node in list(self.nodes):
+                node._on_parameter(event['node'], event['name'], event['value'])
+        elif event['type'] == 'sync':
+            _sync(event['change'])
+        elif event['type'] == 'stop':
+            self.running = False
+
+
+def _sync(change):
+    """Applies a change to the copy of the project in the file system of the worker"""
+    path = change['path']
+    if change['kind'] == 'directory':
+        os.makedirs(path, exist_ok=True)
+    elif change['kind'] == 'file':
+        os.makedirs(os.path.dirname(path), exist_ok=True)
+        with open(path, 'wb') as file:
+            file.write(base64.b64decode(change['data']))
+    elif change['kind'] == 'removed':
+        if os.path.isdir(path):
+            shutil.rmtree(path)
+        elif os.path.exists(path):
+            os.remove(path)
+
+
+_context = None
+
+
+def get_context():
+    if _context is None:
+        raise RuntimeError('rclpy.init() has not been called')
+    return _context
+
+
+def init(*, args=None):
+    global _context
+    _context = _Context()
+
+
+def ok():
+    return _context is not None and _context.running
+
+
+def spin_once(node, *, timeout_sec=None):
+    get_context().spin_once(timeout_sec)
+
+
+def spin(node):
+    while ok():
+        spin_once(node)
+
+
+def shutdown():
+    global _context
+    if _context is not None:
+        _context.running = False
+        for node in list(_context.nodes):
+            node.destroy_node()
+        _context = None
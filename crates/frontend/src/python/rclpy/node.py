@@ -0,0 +1,203 @@
+import rclpy
+from rclpy.parameter import Parameter
+
+
+def _depth(qos_profile):
+    return getattr(qos_profile, 'depth', qos_profile)
+
+
+class Logger:
+    def __init__(self, name):
+        self.name = name
+
+    def _log(self, level, message):
+        rclpy.get_context().send(type='log', level=level, node=self.name, message=str(message))
+
+    def debug(self, message):
+        self._log('DEBUG', message)
+
+    def info(self, message):
+        self._log('INFO', message)
+
+    def warning(self, message):
+        self._log('WARN', message)
+
+    warn = warning
+
+    def error(self, message):
+        self._log('ERROR', message)
+
+    def fatal(self, message):
+        self._log('FATAL', message)
+
+
+class Publisher:
+    def __init__(self, node, msg_type, topic, qos_profile):
+        self.msg_type = msg_type
+        self.topic = topic
+        self._id = node._request('create_publisher', topic, msg_type)['id']
+
+    def publish(self, msg):
+        if not isinstance(msg, self.msg_type):
+            raise TypeError(f'expected {self.msg_type.__name__}, got {type(msg).__name__}')
+        data = {field: getattr(msg, field) for field in msg.__slots__}
+        rclpy.get_context().send(type='publish', publisher=self._id, message={'type': msg._type, 'data': data})
+
+    def destroy(self):
+        rclpy.get_context().send(type='destroy', id=self._id)
+
+
+class Subscription:
+    def __init__(self, node, msg_type, topic, callback, qos_profile):
+        self.msg_type = msg_type
+        self.topic = topic
+        self.callback = callback
+        self._id = node._request('create_subscription', topic, msg_type, depth=_depth(qos_profile))['id']
+
+    def destroy(self):
+        rclpy.get_context().send(type='destroy', id=self._id)
+
+
+class Timer:
+    """Calls back every `timer_period_sec` seconds of simulated time"""
+
+    def __init__(self, timer_period_sec, callback, now):
+        if timer_period_sec <= 0:
+            raise ValueError(f'the timer period must be positive, not {timer_period_sec}')
+        self.timer_period_sec = timer_period_sec
+        self.callback = callback
+        self._next_call = now + timer_period_sec
+        self._cancelled = False
+
+    def cancel(self):
+        self._cancelled = True
+
+    def is_canceled(self):
+        return self._cancelled
+
+    def reset(self):
+        self._cancelled = False
+        self._next_call = rclpy.get_context().time + self.timer_period_sec
+
+    def destroy(self):
+        self.cancel()
+
+
+class Node:
+    def __init__(self, node_name, *, namespace=''):
+        self._name = node_name
+        self._namespace = '/' + namespace.strip('/')
+        self._logger = Logger(node_name)
+        self._publishers = []
+        self._subscriptions = {}
+        self._timers = []
+        self._parameters = {}
+        self._on_set_parameters_callbacks = []
+        rclpy.get_context().nodes.append(self)
+
+    def get_name(self):
+        return self._name
+
+    def get_namespace(self):
+        return self._namespace
+
+    def get_logger(self):
+        return self._logger
+
+    def get_fully_qualified_name(self):
+        return f'{self._namespace.rstrip("/")}/{self._name}'
+
+    def _request(self, request_type, topic, msg_type, **request):
+        return rclpy.get_context().request(type=request_type, node=self._name, namespace=self._namespace,
+            topic=topic, message_type=msg_type._type, **request)
+
+    def create_publisher(self, msg_type, topic, qos_profile):
+        publisher = Publisher(self, msg_type, topic, qos_profile)
+        self._publishers.append(publisher)
+        return publisher
+
+    def create_subscription(self, msg_type, topic, callback, qos_profile):
+        subscription = Subscription(self, msg_type, topic, callback, qos_profile)
+        self._subscriptions[subscription._id] = subscription
+        return subscription
+
+    def create_timer(self, timer_period_sec, callback):
+        timer = Timer(timer_period_sec, callback, rclpy.get_context().time)
+        self._timers.append(timer)
+        return timer
+
+    def declare_parameter(self, name, value=None):
+        """Declares a parameter, a value set in the launch file takes precedence over `value`"""
+        if name in self._parameters:
+            raise RuntimeError(f'parameter {name} has already been declared')
+        reply = rclpy.get_context().request(type='declare_parameter', node=self._name, namespace=self._namespace,
+            name=name, value=Parameter(name, value=value)._encode())
+        parameter = Parameter._decode(name, reply['value'])
+        self._parameters[name] = parameter
+        return parameter
+
+    def declare_parameters(self, namespace, parameters):
+        prefix = f'{namespace}.' if namespace else ''
+        return [self.declare_parameter(prefix + name, value) for name, value in parameters]
+
+    def has_parameter(self, name):
+        return name in self._parameters
+
+    def get_parameter(self, name):
+        if name not in self._parameters:
+            raise RuntimeError(f'parameter {name} has not been declared')
+        return self._parameters[name]
+
+    def get_parameters(self, names):
+        return [self.get_parameter(name) for name in names]
+
+    def add_on_set_parameters_callback(self, callback):
+        """Calls back with a list of the parameters whenever they are changed from
+        the simulator, the value has already been changed, so the result is ignored"""
+        self._on_set_parameters_callbacks.append(callback)
+
+    def remove_on_set_parameters_callback(self, callback):
+        self._on_set_parameters_callbacks.remove(callback)
+
+    def destroy_publisher(self, publisher):
+        self._publishers.remove(publisher)
+        publisher.destroy()
+
+    def destroy_subscription(self, subscription):
+        del self._subscriptions[subscription._id]
+        subscription.destroy()
+
+    def destroy_timer(self, timer):
+        self._timers.remove(timer)
+        timer.destroy()
+
+    def destroy_node(self):
+        for publisher in self._publishers:
+            publisher.destroy()
+        for subscription in self._subscriptions.values():
+            subscription.destroy()
+        self._publishers.clear()
+        self._subscriptions.clear()
+        self._timers.clear()
+        context = rclpy.get_context()
+        if self in context.nodes:
+            context.nodes.remove(self)
+
+    def _on_clock(self, time):
+        for timer in list(self._timers):
+            while not timer._cancelled and time >= timer._next_call:
+                timer._next_call += timer.timer_period_sec
+                timer.callback()
+
+    def _on_parameter(self, node, name, data):
+        if node != self.get_fully_qualified_name() or name not in self._parameters:
+            return
+        parameter = Parameter._decode(name, data)
+        self._parameters[name] = parameter
+        for callback in list(self._on_set_parameters_callbacks):
+            callback([parameter])
+
+    def _on_message(self, subscription_id, data):
+        subscription = self._subscriptions.get(subscription_id)
+        if subscription is not None:
+            subscription.callback(subscription.msg_type(**data))
@@ -0,0 +1,53 @@
+import enum
+
+
+class Parameter:
+    """A named value that a node declared, its type is fixed after that"""
+
+    class Type(enum.Enum):
+        NOT_SET = 'not_set'
+        BOOL = 'bool'
+        INTEGER = 'int'
+        DOUBLE = 'double'
+        STRING = 'str'
+
+        @classmethod
+        def from_parameter_value(cls, value):
+            if value is None:
+                return cls.NOT_SET
+            # bool is a subclass of int, so it has to be checked first
+            if isinstance(value, bool):
+                return cls.BOOL
+            if isinstance(value, int):
+                return cls.INTEGER
+            if isinstance(value, float):
+                return cls.DOUBLE
+            if isinstance(value, str):
+                return cls.STRING
+            raise TypeError(f'parameters can not be of type {type(value).__name__}')
+
+    def __init__(self, name, type_=None, value=None):
+        if type_ is None:
+            type_ = Parameter.Type.from_parameter_value(value)
+        self.name = name
+        self.type_ = type_
+        self.value = value
+
+    def _encode(self):
+        if self.type_ == Parameter.Type.NOT_SET:
+            return None
+        return {'type': self.type_.value, 'value': self.value}
+
+    @classmethod
+    def _decode(cls, name, data):
+        type_ = Parameter.Type(data['type'])
+        convert = {
+            Parameter.Type.BOOL: bool,
+            Parameter.Type.INTEGER: int,
+            Parameter.Type.DOUBLE: float,
+            Parameter.Type.STRING: str,
+        }[type_]
+        return cls(name, type_, convert(data['value']))
+
+    def __repr__(self):
+        return f'Parameter(name={self.name!r}, value={self.value!r})'
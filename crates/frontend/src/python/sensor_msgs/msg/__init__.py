@@ -0,0 +1,34 @@
+class Range:
+    """A distance in meters measured along a single ray, which is `max_range` if nothing was hit"""
+
+    _type = 'sensor_msgs/msg/Range'
+    __slots__ = ('min_range', 'max_range', 'range')
+
+    def __init__(self, min_range=0.0, max_range=0.0, range=0.0):
+        self.min_range = float(min_range)
+        self.max_range = float(max_range)
+        self.range = float(range)
+
+    def __repr__(self):
+        return f'Range(min_range={self.min_range}, max_range={self.max_range}, range={self.range})'
+
+
+class LaserScan:
+    """Distances in meters measured along rays that are `angle_increment` radians apart,
+    starting at `angle_min` relative to the heading and turning counterclockwise"""
+
+    _type = 'sensor_msgs/msg/LaserScan'
+    __slots__ = ('angle_min', 'angle_max', 'angle_increment', 'range_min', 'range_max', 'ranges')
+
+    def __init__(self, angle_min=0.0, angle_max=0.0, angle_increment=0.0, range_min=0.0, range_max=0.0, ranges=()):
+        self.angle_min = float(angle_min)
+        self.angle_max = float(angle_max)
+        self.angle_increment = float(angle_increment)
+        self.range_min = float(range_min)
+        self.range_max = float(range_max)
+        self.ranges = [float(value) for value in ranges]
+
+    def __repr__(self):
+        return (f'LaserScan(angle_min={self.angle_min}, angle_max={self.angle_max}, '
+            f'angle_increment={self.angle_increment}, range_min={self.range_min}, '
+            f'range_max={self.range_max}, ranges=[{len(self.ranges)} values])')
@@ -1,15 +1,35 @@
-use std::rc::Rc;
+use std::{cell::RefCell, rc::Rc};
 
-use dominator::{clone, events, html, svg, Dom};
+use dominator::{clone, events, html, svg, Dom, EventOptions};
 use dominator_bulma::{block, icon, icon_text};
 use futures_signals::{signal::{self, Mutable, Signal, SignalExt}, signal_vec::SignalVecExt};
+use js_sys::{Array, Uint8Array};
+use wasm_bindgen::{prelude::*, JsCast};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, HtmlInputElement, Url};
 
-use crate::vfs::Directory;
+use crate::{contextmenu::ContextMenuState, vfs::{self, Directory, Node}, workspace::activity_panel::{graph, plot, results}};
+
+// some browsers cancel a download if its url is revoked right after the click
+const REVOKE_DELAY_MS: u32 = 1000;
 
 const ICON_SVG_PATH: &str =
     "M16 0H8C6.9 0 6 .9 6 2V18C6 19.1 6.9 20 8 20H20C21.1 20 22 19.1 22 \
      18V6L16 0M20 18H8V2H15V7H20V18M4 4V22H20V24H4C2.9 24 2 23.1 2 22V4H4Z";
 
+const EXPORT_ICON_PATH: &str =
+    "M5,20H19V18H5M19,9H15V3H9V9H5L12,16L19,9Z";
+
+const IMPORT_ICON_PATH: &str =
+    "M9,16V10H5L12,3L19,10H15V16H9M5,20V18H19V20H5Z";
+
+const SIMULATE_ICON_PATH: &str = "M8,5.14V19.14L19,12.14L8,5.14Z";
+
+const RESET_ICON_PATH: &str =
+    "M13,3A9,9 0 0,0 4,12H1L4.89,15.89L4.96,16.03L9,12H6A7,7 0 0,1 13,5A7,7 0 0,1 \
+     20,12A7,7 0 0,1 13,19C11.07,19 9.32,18.21 8.06,16.94L6.64,18.36C8.27,20 10.5,21 \
+     13,21A9,9 0 0,0 22,12A9,9 0 0,0 13,3Z";
+
 fn folder_open_icon() -> Dom {
     const FOLDER_OPEN_ICON: &str = "M2,7 12,17 22,7Z";
     svg!("svg", {
@@ -47,15 +67,242 @@ fn file_icon() -> Dom {
     })
 }
 
+fn action(tooltip: &str, path: &str, on_click: impl FnMut(events::Click) + 'static) -> Dom {
+    icon!({
+        .attr("title", tooltip)
+        .style("cursor", "pointer")
+        .event(on_click)
+        .child(svg!("svg", {
+            .attr("pointer-events", "none")
+            .attr("height", "1em")
+            .attr("viewBox", "0 0 24 24")
+            .class("has-fill-grey-dark")
+            .child(svg!("path", {
+                .attr("d", path)
+            }))
+        }))
+    })
+}
+
+fn download(name: &str, data: &[u8]) -> Result<(), JsValue> {
+    let blob = Blob::new_with_u8_array_sequence_and_options(
+        &Array::of1(&Uint8Array::from(data)),
+        BlobPropertyBag::new().type_("application/zip"))?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+    let anchor = web_sys::window()
+        .unwrap()
+        .document()
+        .unwrap()
+        .create_element("a")?
+        .unchecked_into::<HtmlAnchorElement>();
+    anchor.set_href(&url);
+    anchor.set_download(name);
+    anchor.click();
+    wasm_bindgen_futures::spawn_local(async move {
+        gloo_timers::future::TimeoutFuture::new(REVOKE_DELAY_MS).await;
+        if let Err(error) = Url::revoke_object_url(&url) {
+            tracing::error!("could not revoke {url}: {error:?}");
+        }
+    });
+    Ok(())
+}
+
+fn export_project() {
+    let project = crate::PROJECT.with(|project| Rc::clone(project));
+    let name = format!("{}.zip", project.name.lock_ref());
+    let result = vfs::archive::export(&project)
+        .map_err(|error| JsValue::from(error.to_string()))
+        .and_then(|data| download(&name, &data));
+    if let Err(error) = result {
+        tracing::error!("could not export project: {error:?}");
+    }
+}
+
+fn import_project() {
+    let input = web_sys::window()
+        .unwrap()
+        .document()
+        .unwrap()
+        .create_element("input")
+        .unwrap()
+        .unchecked_into::<HtmlInputElement>();
+    input.set_type("file");
+    input.set_accept(".zip");
+    let on_change = Closure::once_into_js(clone!(input => move || {
+        if let Some(file) = input.files().and_then(|files| files.get(0)) {
+            wasm_bindgen_futures::spawn_local(async move {
+                let data = match JsFuture::from(file.array_buffer()).await {
+                    Ok(buffer) => Uint8Array::new(&buffer).to_vec(),
+                    Err(error) => {
+                        tracing::error!("could not read {}: {error:?}", file.name());
+                        return;
+                    }
+                };
+                let name = crate::PROJECT.with(|project| project.name.get_cloned());
+                match vfs::archive::import(&data, &name) {
+                    Ok(project) => {
+                        let confirmed = web_sys::window()
+                            .unwrap()
+                            .confirm_with_message(&format!("Replace the project with the contents of {}?", file.name()))
+                            .unwrap_or(false);
+                        if confirmed {
+                            crate::PROJECT.with(|current| current.replace(&project));
+                        }
+                    }
+                    Err(error) => tracing::error!("could not import {}: {error}", file.name()),
+                }
+            });
+        }
+    }));
+    input.set_onchange(Some(on_change.unchecked_ref()));
+    input.click();
+}
+
+// the name of a copy of `name` that does not exist in `parent` yet, e.g.,
+// run_copy.py or run_copy2.py
+fn copy_name(parent: &Directory, name: &str) -> String {
+    let (stem, extension) = match name.rfind('.') {
+        Some(index) if index > 0 => name.split_at(index),
+        _ => (name, ""),
+    };
+    (1..)
+        .map(|index| match index {
+            1 => format!("{stem}_copy{extension}"),
+            _ => format!("{stem}_copy{index}{extension}"),
+        })
+        .find(|name| parent.child(name).is_none())
+        .unwrap()
+}
+
+fn prompt(message: &str, default: &str) -> Option<String> {
+    web_sys::window()
+        .unwrap()
+        .prompt_with_message_and_default(message, default)
+        .ok()
+        .flatten()
+        .filter(|answer| answer != default)
+}
+
+/// The context menu of the files and directories in the project
+struct Menu {
+    state: Rc<ContextMenuState>,
+    // the file or directory that the menu was opened on
+    target: RefCell<Option<Node>>,
+}
+
+impl Menu {
+    fn new() -> Rc<Menu> {
+        Rc::new(Menu {
+            state: ContextMenuState::new(),
+            target: Default::default(),
+        })
+    }
+
+    fn open(&self, node: Node, event: &events::ContextMenu) {
+        event.prevent_default();
+        // directories contain the entries that the menu may have been opened on
+        event.stop_propagation();
+        *self.target.borrow_mut() = Some(node);
+        self.state.menu_position.set((event.x(), event.y()));
+        self.state.show_menu.set(true);
+    }
+
+    // runs `action` on the path of the target, which may have changed since
+    // the menu was opened, and logs its errors
+    fn apply(&self, action: impl FnOnce(&Rc<Directory>, &str, &str) -> Result<(), vfs::Error>) {
+        self.state.show_menu.set(false);
+        let Some(node) = self.target.take() else {
+            return;
+        };
+        let project = crate::PROJECT.with(|project| Rc::clone(project));
+        let path = match &node {
+            Node::File(file) => project.path_of(file),
+            Node::Directory(directory) => project.path_of_directory(directory),
+        };
+        let Some(path) = path else {
+            return;
+        };
+        if let Err(error) = action(&project, &path, &node.name()) {
+            tracing::error!("{error}");
+        }
+    }
+
+    fn rename(&self) {
+        self.apply(|project, path, name| match prompt(&format!("Rename {name} to"), name) {
+            Some(new_name) => project.rename(path, &new_name),
+            None => Ok(()),
+        });
+    }
+
+    fn duplicate(&self) {
+        self.apply(|project, path, name| {
+            let parent_path = &path[..path.len() - name.len() - 1];
+            let parent = project.lookup_directory(parent_path)?;
+            project.copy(path, &format!("{parent_path}/{}", copy_name(&parent, name))).map(|_| ())
+        });
+    }
+
+    fn move_to(&self) {
+        self.apply(|project, path, name| match prompt(&format!("Move {name} to"), path) {
+            Some(new_path) => project.move_to(path, &new_path),
+            None => Ok(()),
+        });
+    }
+
+    fn delete(&self) {
+        self.apply(|project, path, name| {
+            let confirmed = web_sys::window()
+                .unwrap()
+                .confirm_with_message(&format!("Delete {name}? This can not be undone."))
+                .unwrap_or(false);
+            match confirmed {
+                true => project.remove(path).map(|_| ()),
+                false => Ok(()),
+            }
+        });
+    }
+
+    fn render(this: &Rc<Menu>) -> Dom {
+        let item = |label: &str, action: fn(&Menu)| html!("a", {
+            .class("dropdown-item")
+            .text(label)
+            .event(clone!(this => move |_: events::Click| {
+                action(&this);
+            }))
+        });
+        html!("div", {
+            .class("dropdown-content")
+            .style("position", "fixed")
+            .style("z-index", "1000")
+            .style_signal("left", this.state.menu_position.signal_ref(|(x, _)| format!("{x}px")))
+            .style_signal("top", this.state.menu_position.signal_ref(|(_, y)| format!("{y}px")))
+            .visible_signal(this.state.show_menu.signal())
+            .global_event(clone!(this => move |_: events::Click| {
+                this.state.show_menu.set_neq(false);
+            }))
+            .children(&mut [
+                item("Rename", Menu::rename),
+                item("Duplicate", Menu::duplicate),
+                item("Move", Menu::move_to),
+                html!("hr", {
+                    .class("dropdown-divider")
+                }),
+                item("Delete", Menu::delete),
+            ])
+        })
+    }
+}
+
 fn render_contents(
     directory: &Rc<Directory>,
     workspace_command_tx: &crate::WorkspaceCommandSender,
+    menu: &Rc<Menu>,
 ) -> Dom {
     let directories = directory.directories
         .signal_vec_cloned()
         .sort_by_cloned(|left_directory, right_directory|
             left_directory.name.lock_ref().cmp(&*right_directory.name.lock_ref()))
-        .map(clone!(workspace_command_tx => move |directory| {
+        .map(clone!(workspace_command_tx, menu => move |directory| {
             let expanded = Mutable::new(true);
             html!("li", {
                 .class("pl-5")
@@ -66,6 +313,9 @@ fn render_contents(
                         let mut expanded = expanded.lock_mut();
                         *expanded = !*expanded;
                     }))
+                    .event_with_options(&EventOptions::preventable(), clone!(menu, directory => move |event: events::ContextMenu| {
+                        menu.open(Node::Directory(directory.clone()), &event);
+                    }))
                     .child(icon!("mr-0", {
                         .child_signal(expanded.signal_ref(|expanded| match expanded {
                             true => folder_open_icon(),
@@ -76,8 +326,8 @@ fn render_contents(
                         .text_signal(directory.name.signal_cloned())
                     }))
                 }))
-                .child_signal(expanded.signal_ref(clone!(directory, workspace_command_tx => move |expanded| match expanded {
-                    true => Some(render_contents(&directory, &workspace_command_tx)),
+                .child_signal(expanded.signal_ref(clone!(directory, workspace_command_tx, menu => move |expanded| match expanded {
+                    true => Some(render_contents(&directory, &workspace_command_tx, &menu)),
                     _ => None
                 })))
             })
@@ -87,15 +337,21 @@ fn render_contents(
         .signal_vec_cloned()
         .sort_by_cloned(|left_file, right_file|
             left_file.name.lock_ref().cmp(&*right_file.name.lock_ref()))
-        .map(clone!(workspace_command_tx => move |file| html!("li", {
+        .map(clone!(workspace_command_tx, menu => move |file| html!("li", {
             .class("pl-5")
             .class("pt-1")
             .child(icon_text!({
                 .style("cursor", "pointer")
-                .event(clone!(workspace_command_tx, file => move |_: events::PointerDown| {
-                    workspace_command_tx
-                        .unbounded_send(crate::WorkspaceCommand::OpenFile(file.clone()))
-                        .unwrap()
+                .event(clone!(workspace_command_tx, file => move |event: events::PointerDown| {
+                    // the secondary button opens the context menu instead
+                    if matches!(event.button(), events::MouseButton::Left) {
+                        workspace_command_tx
+                            .unbounded_send(crate::WorkspaceCommand::OpenFile(file.clone()))
+                            .unwrap()
+                    }
+                }))
+                .event_with_options(&EventOptions::preventable(), clone!(menu, file => move |event: events::ContextMenu| {
+                    menu.open(Node::File(file.clone()), &event);
                 }))
                 .child(icon!("mr-0", {
                     .child(file_icon())
@@ -113,13 +369,15 @@ fn render_contents(
 }
 
 pub struct Explorer {
-    workspace: Rc<Directory>
+    workspace: Rc<Directory>,
+    menu: Rc<Menu>,
 }
 
 impl Default for Explorer {
     fn default() -> Self {
         Self {
-            workspace: crate::PROJECT.with(|workspace| Rc::clone(workspace))
+            workspace: crate::PROJECT.with(|workspace| Rc::clone(workspace)),
+            menu: Menu::new(),
         }
     }
 }
@@ -131,6 +389,7 @@ impl Explorer {
             .class("has-background-white-ter")
             .style("height", "100vh")
             .child(block!("p-3", "m-0", {
+                .style("display", "flex")
                 .child(icon_text!({
                     .child(html!("span", {
                         .style("font-size", ".75em")
@@ -139,6 +398,43 @@ impl Explorer {
                         .text("Explorer")
                     }))
                 }))
+                .child(icon_text!("ml-auto", {
+                    .child(action("Open simulator", SIMULATE_ICON_PATH, clone!(workspace_command_tx => move |_: events::Click| {
+                        workspace_command_tx
+                            .unbounded_send(crate::WorkspaceCommand::OpenSimulator)
+                            .unwrap()
+                    })))
+                    .child(action("Open graph", graph::ICON_PATH, clone!(workspace_command_tx => move |_: events::Click| {
+                        workspace_command_tx
+                            .unbounded_send(crate::WorkspaceCommand::OpenGraph)
+                            .unwrap()
+                    })))
+                    .child(action("Open plot", plot::ICON_PATH, clone!(workspace_command_tx => move |_: events::Click| {
+                        workspace_command_tx
+                            .unbounded_send(crate::WorkspaceCommand::OpenPlot)
+                            .unwrap()
+                    })))
+                    .child(action("Open results", results::ICON_PATH, clone!(workspace_command_tx => move |_: events::Click| {
+                        workspace_command_tx
+                            .unbounded_send(crate::WorkspaceCommand::OpenResults)
+                            .unwrap()
+                    })))
+                    .child(action("Import project", IMPORT_ICON_PATH, |_: events::Click| {
+                        import_project();
+                    }))
+                    .child(action("Export project", EXPORT_ICON_PATH, |_: events::Click| {
+                        export_project();
+                    }))
+                    .child(action("Reset project", RESET_ICON_PATH, |_: events::Click| {
+                        let confirmed = web_sys::window()
+                            .unwrap()
+                            .confirm_with_message("Replace the project with the default template?")
+                            .unwrap_or(false);
+                        if confirmed {
+                            crate::persistence::reset();
+                        }
+                    }))
+                }))
             }))
             // project listing
             .child(html!("ul", {
@@ -161,11 +457,12 @@ impl Explorer {
                         }))
                     }))
                     .child_signal(expanded.signal_ref(clone!(this, workspace_command_tx => move |expanded| match expanded {
-                        true => Some(render_contents(&this.workspace, &workspace_command_tx)),
+                        true => Some(render_contents(&this.workspace, &workspace_command_tx, &this.menu)),
                         _ => None
                     })))
                 }))
             }))
+            .child(Menu::render(&this.menu))
         })
     }
 
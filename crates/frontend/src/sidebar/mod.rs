@@ -5,7 +5,9 @@ use dominator_bulma::{block, column, columns, image};
 use futures_signals::{map_ref, signal::{self, Mutable, Signal, SignalExt}};
 
 pub mod explorer;
+pub mod parameters;
 pub mod search;
+pub mod topics;
 
 const DEFAULT_PANEL_SIZE: u32 = 200;
 const MENU_SIZE_PX: u32 = 50;
@@ -14,7 +16,9 @@ const RESIZER_PX: u32 = 4;
 enum Panel {
     // Not sure if this Rc is necessary?
     Explorer(Rc<explorer::Explorer>),
-    Search(search::Search)
+    Search(search::Search),
+    Topics(Rc<topics::Topics>),
+    Parameters(Rc<parameters::Parameters>),
 }
 
 impl Panel {
@@ -22,6 +26,8 @@ impl Panel {
         match self {
             Panel::Explorer(explorer) => explorer.tooltip(),
             Panel::Search(search) => search.tooltip(),
+            Panel::Topics(topics) => topics.tooltip(),
+            Panel::Parameters(parameters) => parameters.tooltip(),
         }
     }
     
@@ -29,6 +35,8 @@ impl Panel {
         match self {
             Panel::Explorer(explorer) => explorer.icon(active),
             Panel::Search(search) => search.icon(active),
+            Panel::Topics(topics) => topics.icon(active),
+            Panel::Parameters(parameters) => parameters.icon(active),
         }
     }
 
@@ -36,6 +44,8 @@ impl Panel {
         match self {
             Panel::Explorer(explorer) => explorer::Explorer::render(explorer, workspace_command_tx),
             Panel::Search(search) => search.render(),
+            Panel::Topics(topics) => topics::Topics::render(topics),
+            Panel::Parameters(parameters) => parameters::Parameters::render(parameters),
         }
     }
 }
@@ -56,7 +66,9 @@ impl Default for Sidebar {
         Self {
             panels: vec![
                 explorer.clone(),
-                Rc::new(Panel::Search(search::Search::default()))
+                Rc::new(Panel::Search(search::Search::default())),
+                Rc::new(Panel::Topics(Default::default())),
+                Rc::new(Panel::Parameters(Default::default())),
             ],
             // hack
             active_panel: Mutable::new(Some(explorer)),
@@ -0,0 +1,149 @@
+use std::rc::Rc;
+
+use dominator::{clone, events, html, svg, Dom};
+use dominator_bulma::{block, icon_text};
+use futures::StreamExt;
+use futures_signals::{signal::{self, Mutable, Signal, SignalExt}, signal_vec::{MutableVec, SignalVecExt}};
+use gloo_timers::future::IntervalStream;
+use web_sys::HtmlInputElement;
+
+use crate::{bus::{ParameterInfo, ParameterValue}, log};
+
+const ICON_SVG_PATH: &str = "M3,17V19H9V17H3M3,5V7H13V5H3M13,21V19H21V17H13V15H11V21H13M7,9V11H3V13H7V15H9V9H7M21,\
+    13V11H11V13H21M15,9H17V7H21V5H17V3H15V9Z";
+
+// the interval between refreshes of the list in milliseconds
+const REFRESH_MS: u32 = 500;
+
+/// Lists the parameters that the running nodes declared and changes them
+#[derive(Default)]
+pub struct Parameters {
+    rows: MutableVec<ParameterInfo>,
+}
+
+impl Parameters {
+    pub fn tooltip(&self) -> &'static str {
+        "Parameters"
+    }
+
+    pub fn icon(&self, active: impl Signal<Item = bool> + 'static) -> Dom {
+        let active = active.broadcast();
+        svg!("svg", {
+            .attr("viewBox", "0 0 24 24")
+            .class_signal("has-fill-white", active.signal())
+            .class_signal("has-fill-grey", signal::not(active.signal()))
+            .child(svg!("path", {
+                .attr("d", ICON_SVG_PATH)
+            }))
+        })
+    }
+
+    // parameters come and go with the nodes, so the list is refreshed while the panel is shown
+    async fn refresh(this: Rc<Parameters>) {
+        let bus = crate::BUS.with(|bus| Rc::clone(bus));
+        let mut interval = IntervalStream::new(REFRESH_MS);
+        loop {
+            let rows = bus.parameters();
+            if *this.rows.lock_ref() != rows[..] {
+                this.rows.lock_mut().replace_cloned(rows);
+            }
+            if interval.next().await.is_none() {
+                break;
+            }
+        }
+    }
+
+    fn set(row: &ParameterInfo, value: ParameterValue) -> Result<(), String> {
+        let bus = crate::BUS.with(|bus| Rc::clone(bus));
+        bus.set_parameter(&row.node, &row.name, value)
+            .map_err(|error| error.to_string())
+    }
+
+    fn render_input(row: ParameterInfo) -> Dom {
+        let invalid = Mutable::new(false);
+        match row.value {
+            ParameterValue::Bool(value) => html!("input" => HtmlInputElement, {
+                .attr("type", "checkbox")
+                .prop("checked", value)
+                .with_node!(input => {
+                    .event(move |_: events::Change| {
+                        if let Err(error) = Self::set(&row, ParameterValue::Bool(input.checked())) {
+                            log::warn(log::SIMULATOR, &error);
+                        }
+                    })
+                })
+            }),
+            _ => html!("input" => HtmlInputElement, {
+                .class("input")
+                .class("is-small")
+                .class_signal("is-danger", invalid.signal())
+                .attr("type", match row.value {
+                    ParameterValue::String(_) => "text",
+                    _ => "number",
+                })
+                .apply_if(matches!(row.value, ParameterValue::Double(_)), |dom| dom.attr("step", "any"))
+                .prop("value", &row.value.to_string())
+                .with_node!(input => {
+                    // the value is only set once editing is done, so that nodes do not see half typed numbers
+                    .event(clone!(invalid => move |_: events::Change| {
+                        let value = ParameterValue::parse_as(row.value.type_name(), &input.value());
+                        let result = value.ok_or_else(|| format!("{} must be of type {}", row.name, row.value.type_name()))
+                            .and_then(|value| Self::set(&row, value));
+                        invalid.set_neq(result.is_err());
+                        if let Err(error) = result {
+                            log::warn(log::SIMULATOR, &error);
+                        }
+                    }))
+                })
+            }),
+        }
+    }
+
+    fn render_row(row: ParameterInfo) -> Dom {
+        html!("li", {
+            .class("px-3")
+            .class("py-1")
+            .child(html!("div", {
+                .class("is-size-7")
+                .child(html!("span", {
+                    .class("is-family-monospace")
+                    .text(&row.name)
+                }))
+                .child(html!("span", {
+                    .class("has-text-grey")
+                    .text(&format!(" \u{b7} {} \u{b7} {}", row.value.type_name(), row.node))
+                }))
+            }))
+            .child(Self::render_input(row))
+        })
+    }
+
+    pub fn render(this: &Rc<Parameters>) -> Dom {
+        block!({
+            .class("has-background-white-ter")
+            .style("height", "100vh")
+            .style("overflow-y", "auto")
+            .future(Self::refresh(this.clone()))
+            .child(block!("p-3", "m-0", {
+                .child(icon_text!({
+                    .child(html!("span", {
+                        .style("font-size", ".75em")
+                        .style("letter-spacing", ".1em")
+                        .style("text-transform", "uppercase")
+                        .text("Parameters")
+                    }))
+                }))
+            }))
+            .child(html!("p", {
+                .class("px-3")
+                .class("is-size-7")
+                .class("has-text-grey")
+                .visible_signal(this.rows.signal_vec_cloned().is_empty())
+                .text("No parameters, nodes declare them with declare_parameter while the simulator runs")
+            }))
+            .child(html!("ul", {
+                .children_signal_vec(this.rows.signal_vec_cloned().map(Self::render_row))
+            }))
+        })
+    }
+}
@@ -0,0 +1,225 @@
+use std::{collections::{BTreeMap, VecDeque}, rc::Rc};
+
+use dominator::{clone, events, html, svg, Dom};
+use dominator_bulma::{block, icon_text};
+use futures::StreamExt;
+use futures_signals::{signal::{self, Mutable, Signal, SignalExt}, signal_vec::{MutableVec, SignalVecExt}};
+use gloo_timers::future::IntervalStream;
+
+use crate::bus::{Message, TopicInfo};
+
+const ICON_SVG_PATH: &str = "M21,9L17,5V8H10V10H17V13M7,11L3,15L7,19V16H14V14H7V11Z";
+
+// the interval between refreshes of the list in milliseconds
+const REFRESH_MS: u32 = 500;
+// rates are averaged over this many seconds of wall time
+const RATE_WINDOW: f64 = 2.0;
+// the number of messages shown by the echo
+const ECHO_DEPTH: usize = 5;
+
+/// A topic along with the number of messages per second published on it
+#[derive(Debug, Clone, PartialEq)]
+struct Row {
+    info: TopicInfo,
+    rate: f64,
+}
+
+/// Counts the messages on each topic between refreshes, like `ros2 topic hz`
+#[derive(Default)]
+struct RateMeter {
+    // the time of each refresh in seconds and the number of messages since the previous one
+    history: BTreeMap<String, VecDeque<(f64, usize)>>,
+}
+
+impl RateMeter {
+    fn update(&mut self, now: f64, counts: &BTreeMap<String, usize>, topics: &[TopicInfo]) {
+        self.history.retain(|name, _| topics.iter().any(|topic| topic.name == *name));
+        for topic in topics {
+            let history = self.history.entry(topic.name.clone()).or_default();
+            history.push_back((now, counts.get(&topic.name).copied().unwrap_or(0)));
+            while history.front().is_some_and(|(time, _)| now - time > RATE_WINDOW) {
+                history.pop_front();
+            }
+        }
+    }
+
+    // the messages that arrived after the oldest refresh in the window divided by its duration
+    fn rate(&self, topic: &str) -> f64 {
+        let Some(history) = self.history.get(topic) else {
+            return 0.0;
+        };
+        match (history.front(), history.back()) {
+            (Some((first, _)), Some((last, _))) if last > first =>
+                history.iter().skip(1).map(|(_, count)| *count).sum::<usize>() as f64 / (last - first),
+            _ => 0.0,
+        }
+    }
+}
+
+/// Lists the topics on the bus and echoes the messages of the selected topic
+#[derive(Default)]
+pub struct Topics {
+    rows: MutableVec<Row>,
+    selected: Mutable<Option<String>>,
+    // the most recent messages on the selected topic, the newest first
+    echo: MutableVec<String>,
+}
+
+impl Topics {
+    pub fn tooltip(&self) -> &'static str {
+        "Topics"
+    }
+
+    pub fn icon(&self, active: impl Signal<Item = bool> + 'static) -> Dom {
+        let active = active.broadcast();
+        svg!("svg", {
+            .attr("viewBox", "0 0 24 24")
+            .class_signal("has-fill-white", active.signal())
+            .class_signal("has-fill-grey", signal::not(active.signal()))
+            .child(svg!("path", {
+                .attr("d", ICON_SVG_PATH)
+            }))
+        })
+    }
+
+    fn echo(&self, message: &Message) {
+        // only the fields are shown since the type is listed with the topic
+        let text = serde_json::to_value(message)
+            .ok()
+            .and_then(|mut value| value.get_mut("data").map(serde_json::Value::take))
+            .map(|data| data.to_string())
+            .unwrap_or_default();
+        let mut echo = self.echo.lock_mut();
+        echo.insert_cloned(0, text);
+        echo.truncate(ECHO_DEPTH);
+    }
+
+    // watches the bus while the panel is shown
+    async fn monitor(this: Rc<Topics>) {
+        let bus = crate::BUS.with(|bus| Rc::clone(bus));
+        let monitor = bus.create_monitor();
+        let mut meter = RateMeter::default();
+        let mut interval = IntervalStream::new(REFRESH_MS);
+        loop {
+            let mut counts = BTreeMap::<String, usize>::new();
+            let mut echoed = Vec::new();
+            let selected = this.selected.get_cloned();
+            for (_, topic, message) in monitor.drain() {
+                *counts.entry(topic.clone()).or_default() += 1;
+                if selected.as_ref() == Some(&topic) {
+                    echoed.push(message);
+                }
+            }
+            for message in echoed.iter().skip(echoed.len().saturating_sub(ECHO_DEPTH)) {
+                this.echo(message);
+            }
+            let topics = bus.topics();
+            meter.update(js_sys::Date::now() / 1000.0, &counts, &topics);
+            let rows = topics.into_iter()
+                .map(|info| Row { rate: meter.rate(&info.name), info })
+                .collect::<Vec<_>>();
+            if *this.rows.lock_ref() != rows[..] {
+                this.rows.lock_mut().replace_cloned(rows);
+            }
+            if interval.next().await.is_none() {
+                break;
+            }
+        }
+    }
+
+    fn render_row(this: &Rc<Topics>, row: Row) -> Dom {
+        let name = row.info.name.clone();
+        let selected = this.selected.signal_ref(clone!(name => move |selected| selected.as_ref() == Some(&name)))
+            .broadcast();
+        html!("li", {
+            .class("px-3")
+            .class("py-1")
+            .style("cursor", "pointer")
+            .class_signal("has-background-white-bis", selected.signal())
+            .event(clone!(this => move |_: events::Click| {
+                let mut selected = this.selected.lock_mut();
+                *selected = match selected.as_ref() == Some(&name) {
+                    true => None,
+                    false => Some(name.clone()),
+                };
+                this.echo.lock_mut().clear();
+            }))
+            .child(html!("div", {
+                .class("is-family-monospace")
+                .class("is-size-7")
+                .class_signal("has-text-weight-bold", selected.signal())
+                .text(&row.info.name)
+            }))
+            .child(html!("div", {
+                .class("is-size-7")
+                .class("has-text-grey")
+                .text(&format!("{} \u{b7} {} pub \u{b7} {} sub \u{b7} {:.1} Hz",
+                    row.info.message_type,
+                    row.info.publishers.len(),
+                    row.info.subscriptions.len(),
+                    row.rate))
+            }))
+        })
+    }
+
+    fn render_echo(this: &Rc<Topics>) -> Dom {
+        html!("div", {
+            .child_signal(this.selected.signal_cloned().map(clone!(this => move |selected| {
+                selected.map(|topic| block!("p-3", "m-0", {
+                    .child(html!("div", {
+                        .class("is-size-7")
+                        .class("has-text-weight-bold")
+                        .class("mb-2")
+                        .text(&format!("Echo {topic}"))
+                    }))
+                    .child(html!("p", {
+                        .class("is-size-7")
+                        .class("has-text-grey")
+                        .visible_signal(this.echo.signal_vec_cloned().is_empty())
+                        .text("Waiting for messages")
+                    }))
+                    .children_signal_vec(this.echo.signal_vec_cloned().map(|message| html!("pre", {
+                        .class("is-size-7")
+                        .class("p-2")
+                        .class("mb-1")
+                        .style("white-space", "pre-wrap")
+                        .style("word-break", "break-all")
+                        .text(&message)
+                    })))
+                }))
+            })))
+        })
+    }
+
+    pub fn render(this: &Rc<Topics>) -> Dom {
+        block!({
+            .class("has-background-white-ter")
+            .style("height", "100vh")
+            .style("overflow-y", "auto")
+            .future(Self::monitor(this.clone()))
+            .child(block!("p-3", "m-0", {
+                .child(icon_text!({
+                    .child(html!("span", {
+                        .style("font-size", ".75em")
+                        .style("letter-spacing", ".1em")
+                        .style("text-transform", "uppercase")
+                        .text("Topics")
+                    }))
+                }))
+            }))
+            .child(html!("p", {
+                .class("px-3")
+                .class("is-size-7")
+                .class("has-text-grey")
+                .visible_signal(this.rows.signal_vec_cloned().is_empty())
+                .text("No topics, start the simulator to create some")
+            }))
+            .child(html!("ul", {
+                .children_signal_vec(this.rows.signal_vec_cloned().map(clone!(this => move |row| {
+                    Self::render_row(&this, row)
+                })))
+            }))
+            .child(Self::render_echo(this))
+        })
+    }
+}
@@ -0,0 +1,42 @@
+/// The speeds that the simulation can run at relative to wall time
+pub const SPEEDS: [f64; 5] = [0.25, 0.5, 1.0, 2.0, 4.0];
+
+// limits the work done in one update, e.g., after the page was in the
+// background, at the cost of the simulation falling behind wall time
+const MAX_STEPS_PER_UPDATE: u32 = 20;
+
+/// Turns elapsed wall time into a whole number of fixed size simulation steps,
+/// carrying the remainder over to the next update
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clock {
+    /// The duration of a step in seconds of simulated time
+    pub step: f64,
+    accumulated: f64,
+}
+
+impl Clock {
+    pub fn new(step: f64) -> Clock {
+        Clock {
+            step,
+            accumulated: 0.0,
+        }
+    }
+
+    /// Returns the number of steps to take after `elapsed` seconds of wall time
+    /// have passed while running at `speed`
+    pub fn advance(&mut self, elapsed: f64, speed: f64) -> u32 {
+        self.accumulated += elapsed.max(0.0) * speed;
+        let steps = (self.accumulated / self.step).floor();
+        if steps >= f64::from(MAX_STEPS_PER_UPDATE) {
+            self.accumulated = 0.0;
+            return MAX_STEPS_PER_UPDATE;
+        }
+        self.accumulated -= steps * self.step;
+        steps as u32
+    }
+
+    /// Discards any accumulated time, e.g., when the simulation is paused
+    pub fn reset(&mut self) {
+        self.accumulated = 0.0;
+    }
+}
@@ -0,0 +1,595 @@
+use std::{collections::BTreeSet, f64::consts::PI, fmt, rc::Rc};
+
+use crate::{
+    bus::{self, message::{self, Contact, LaserScan, Odometry, Range}, Bus, Message, MessageType},
+    launch::{self, Collision, ModelType, SensorType},
+};
+
+pub mod clock;
+pub mod noise;
+pub mod scenario;
+
+use noise::Noise;
+
+/// A position on the ground plane and a heading, i.e., the rotation about the
+/// y axis in radians. A heading of zero faces along the x axis and positive
+/// headings turn counterclockwise when the world is viewed from above.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Pose {
+    pub x: f64,
+    pub z: f64,
+    pub heading: f64,
+}
+
+impl Pose {
+    pub fn from_launch(pose: &launch::Pose) -> Pose {
+        Pose {
+            x: pose.position[0],
+            z: pose.position[2],
+            heading: normalize_angle(pose.orientation[1]),
+        }
+    }
+
+    /// The unit vector in the direction of the heading
+    pub fn direction(&self) -> (f64, f64) {
+        (self.heading.cos(), -self.heading.sin())
+    }
+}
+
+/// Wraps an angle into the range (-pi, pi]
+pub fn normalize_angle(angle: f64) -> f64 {
+    let angle = angle.rem_euclid(2.0 * PI);
+    match angle > PI {
+        true => angle - 2.0 * PI,
+        false => angle,
+    }
+}
+
+/// The geometry and limits of a robot with two independently driven wheels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DifferentialDrive {
+    pub wheel_radius: f64,
+    pub wheel_separation: f64,
+    /// The maximum angular velocity of each wheel in rad/s
+    pub max_wheel_velocity: f64,
+}
+
+impl DifferentialDrive {
+    /// Returns the linear velocity in m/s and the angular velocity in rad/s
+    /// for the given wheel velocities, see [Robot::wheel_velocities]
+    pub fn velocity(&self, (left, right): (f64, f64)) -> (f64, f64) {
+        let left = self.wheel_radius * left.clamp(-self.max_wheel_velocity, self.max_wheel_velocity);
+        let right = -self.wheel_radius * right.clamp(-self.max_wheel_velocity, self.max_wheel_velocity);
+        ((left + right) / 2.0, (right - left) / self.wheel_separation)
+    }
+
+    /// Integrates the motion along a circular arc, which is exact for constant
+    /// wheel velocities
+    pub fn integrate(&self, pose: Pose, wheel_velocities: (f64, f64), dt: f64) -> Pose {
+        let (linear, angular) = self.velocity(wheel_velocities);
+        let heading = pose.heading + angular * dt;
+        let (dx, dz) = match angular.abs() < 1e-9 {
+            true => (linear * dt * pose.heading.cos(), -linear * dt * pose.heading.sin()),
+            false => {
+                let radius = linear / angular;
+                (radius * (heading.sin() - pose.heading.sin()), radius * (heading.cos() - pose.heading.cos()))
+            }
+        };
+        Pose {
+            x: pose.x + dx,
+            z: pose.z + dz,
+            heading: normalize_angle(heading),
+        }
+    }
+}
+
+/// Dimensions of the TurtleBot3 Burger
+pub const TURTLEBOT_DRIVE: DifferentialDrive = DifferentialDrive {
+    wheel_radius: 0.033,
+    wheel_separation: 0.160,
+    max_wheel_velocity: 6.67,
+};
+pub const TURTLEBOT_RADIUS: f64 = 0.105;
+/// The number of velocity commands that are queued for each robot between steps
+pub const VELOCITY_QUEUE_DEPTH: usize = 10;
+/// The standard deviation of the wheel velocities measured for odometry relative to their true values
+pub const ODOMETRY_NOISE: f64 = 0.05;
+/// The maximum systematic error of the measured wheel velocities, which makes the odometry drift
+pub const ODOMETRY_DRIFT: f64 = 0.02;
+/// The topic that a [Contact] is published on whenever a robot starts touching something
+pub const CONTACTS_TOPIC: &str = "/contacts";
+/// The name that contacts with the boundary of the world use for the other model
+pub const BOUNDARY: &str = "boundary";
+// the node that publishes the messages of the world itself
+const WORLD_NODE: &str = "/simulator";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    Bus(bus::Error),
+    /// A robot starts out touching the boundary of the world or another model,
+    /// which would keep it from moving when collisions stop the robots
+    Overlap {
+        model: String,
+        other: String,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Bus(error) => error.fmt(f),
+            Error::Overlap { model, other } if other == BOUNDARY =>
+                write!(f, "{model} starts overlapping the boundary of the world"),
+            Error::Overlap { model, other } => write!(f, "{model} starts overlapping {other}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<bus::Error> for Error {
+    fn from(error: bus::Error) -> Self {
+        Error::Bus(error)
+    }
+}
+
+/// A range sensor or lidar that is mounted at the center of a robot
+pub struct Sensor {
+    pub name: String,
+    pub sensor_type: SensorType,
+    /// The maximum distance in meters that can be measured
+    pub range: f64,
+    /// The distances that were measured most recently along [Sensor::angles]
+    pub ranges: Vec<f64>,
+    period: f64,
+    next_time: f64,
+    publisher: bus::Publisher,
+}
+
+impl Sensor {
+    /// The angles of the rays relative to the heading of the robot
+    pub fn angles(&self) -> Vec<f64> {
+        match self.sensor_type {
+            SensorType::Range { angle } => vec![angle],
+            SensorType::Lidar { samples } => (0..samples)
+                .map(|index| 2.0 * PI * f64::from(index) / f64::from(samples))
+                .collect(),
+        }
+    }
+
+    fn message(&self, ranges: Vec<f64>) -> Message {
+        match self.sensor_type {
+            SensorType::Range { .. } => Range {
+                min_range: 0.0,
+                max_range: self.range,
+                range: ranges[0],
+            }.into(),
+            SensorType::Lidar { samples } => LaserScan {
+                angle_min: 0.0,
+                angle_max: 2.0 * PI * f64::from(samples - 1) / f64::from(samples),
+                angle_increment: 2.0 * PI / f64::from(samples),
+                range_min: 0.0,
+                range_max: self.range,
+                ranges,
+            }.into(),
+        }
+    }
+}
+
+pub struct Robot {
+    pub name: String,
+    pub model_type: ModelType,
+    pub drive: DifferentialDrive,
+    pub radius: f64,
+    pub pose: Pose,
+    /// The angular velocities of the left and right wheels in rad/s. The
+    /// wheels are mounted facing away from each other, so driving forwards
+    /// requires a positive left and a negative right velocity.
+    pub wheel_velocities: (f64, f64),
+    pub sensors: Vec<Sensor>,
+    /// The pose as estimated from the wheel velocities, which starts at the true pose
+    pub odometry: Pose,
+    // the source of noise and the systematic errors of the left and right
+    // wheels, or none if the odometry is exact
+    odometry_noise: Option<(Noise, (f64, f64))>,
+    // velocity commands sent to all robots and to this robot only
+    velocity_subscriptions: [bus::Subscription; 2],
+    odometry_publisher: bus::Publisher,
+    pose_publisher: bus::Publisher,
+}
+
+impl Robot {
+    /// Creates a robot that is driven by [bus::message::Velocity] messages on
+    /// both `/velocity` and `/<name>/velocity`. It publishes its odometry on
+    /// `/<name>/odom`, its true pose on `/<name>/pose` and the measurements of
+    /// its sensors on `/<name>/<sensor>`.
+    pub fn new(model: &launch::Model, drive: DifferentialDrive, radius: f64, noise: bool, bus: &Rc<Bus>) -> Result<Robot, bus::Error> {
+        let namespace = format!("/{}", model.name);
+        let subscribe = |topic: &str| bus::resolve_name(&namespace, topic)
+            .and_then(|topic| bus.create_subscription(&namespace, &topic, MessageType::Velocity, VELOCITY_QUEUE_DEPTH));
+        let sensors = model.sensors.iter()
+            .map(|sensor| {
+                let message_type = match sensor.sensor_type {
+                    SensorType::Range { .. } => MessageType::Range,
+                    SensorType::Lidar { .. } => MessageType::LaserScan,
+                };
+                let topic = bus::resolve_name(&namespace, &sensor.name)?;
+                Ok(Sensor {
+                    name: sensor.name.clone(),
+                    sensor_type: sensor.sensor_type,
+                    range: sensor.range,
+                    ranges: Vec::new(),
+                    period: 1.0 / sensor.rate,
+                    next_time: 0.0,
+                    publisher: bus.create_publisher(&namespace, &topic, message_type)?,
+                })
+            })
+            .collect::<Result<_, bus::Error>>()?;
+        let odometry_noise = noise.then(|| {
+            let mut noise = Noise::from_name(&model.name);
+            let mut drift = || ODOMETRY_DRIFT * (2.0 * noise.uniform() - 1.0);
+            let drift = (drift(), drift());
+            (noise, drift)
+        });
+        let publish = |topic: &str, message_type| bus::resolve_name(&namespace, topic)
+            .and_then(|topic| bus.create_publisher(&namespace, &topic, message_type));
+        let pose = Pose::from_launch(&model.pose);
+        Ok(Robot {
+            name: model.name.clone(),
+            model_type: model.model_type,
+            drive,
+            radius,
+            pose,
+            wheel_velocities: (0.0, 0.0),
+            sensors,
+            odometry: pose,
+            odometry_noise,
+            odometry_publisher: publish("odom", MessageType::Odometry)?,
+            pose_publisher: publish("pose", MessageType::Pose)?,
+            velocity_subscriptions: [subscribe("/velocity")?, subscribe("velocity")?],
+        })
+    }
+
+    // applies the most recent velocity command, commands sent to this robot
+    // only take precedence over commands sent to all robots
+    fn receive(&mut self) {
+        let [all, own] = &self.velocity_subscriptions;
+        let (all, own) = (all.drain().pop(), own.drain().pop());
+        if let Some(Message::Velocity(velocity)) = own.or(all) {
+            self.wheel_velocities = (velocity.left, velocity.right);
+        }
+    }
+
+    // integrates the wheel velocities as they would be measured by the robot
+    fn update_odometry(&mut self, dt: f64) {
+        let (left, right) = self.wheel_velocities;
+        let measured = match self.odometry_noise.as_mut() {
+            Some((noise, (left_drift, right_drift))) => (
+                left * noise.gaussian(1.0 + *left_drift, ODOMETRY_NOISE),
+                right * noise.gaussian(1.0 + *right_drift, ODOMETRY_NOISE),
+            ),
+            None => (left, right),
+        };
+        self.odometry = self.drive.integrate(self.odometry, measured, dt);
+        let (linear, angular) = self.drive.velocity(measured);
+        self.odometry_publisher.publish(Odometry {
+            x: self.odometry.x,
+            z: self.odometry.z,
+            heading: self.odometry.heading,
+            linear,
+            angular,
+        }).expect("odometry is published with its own type");
+    }
+
+    fn publish_pose(&self) {
+        self.pose_publisher.publish(message::Pose {
+            x: self.pose.x,
+            z: self.pose.z,
+            heading: self.pose.heading,
+        }).expect("poses are published with their own type");
+    }
+}
+
+/// A box that does not move
+#[derive(Debug, Clone, PartialEq)]
+pub struct Obstacle {
+    pub name: String,
+    pub pose: Pose,
+    /// The extent along the x and z axes before rotation
+    pub size: [f64; 2],
+}
+
+impl Obstacle {
+    // the axes of the box, which are rotated by its heading
+    fn axes(&self) -> [(f64, f64); 2] {
+        let (x, z) = self.pose.direction();
+        [(x, z), (-z, x)]
+    }
+
+    // returns the distance along a ray to the box, if it hits it
+    fn cast(&self, (x, z): (f64, f64), (dx, dz): (f64, f64)) -> Option<f64> {
+        let [u, v] = self.axes();
+        let (ox, oz) = (x - self.pose.x, z - self.pose.z);
+        // intersect the slabs of both axes in the coordinates of the box
+        let mut near = f64::NEG_INFINITY;
+        let mut far = f64::INFINITY;
+        for (axis, half) in [(u, self.size[0] / 2.0), (v, self.size[1] / 2.0)] {
+            let origin = ox * axis.0 + oz * axis.1;
+            let direction = dx * axis.0 + dz * axis.1;
+            if direction.abs() < 1e-12 {
+                if origin.abs() > half {
+                    return None;
+                }
+                continue;
+            }
+            let (a, b) = ((-half - origin) / direction, (half - origin) / direction);
+            near = near.max(a.min(b));
+            far = far.min(a.max(b));
+        }
+        (near <= far && far >= 0.0).then_some(near.max(0.0))
+    }
+
+    // returns the overlap of a circle with this box, see [Overlap]
+    fn overlap(&self, x: f64, z: f64, radius: f64) -> Option<Overlap> {
+        let [u, v] = self.axes();
+        let (dx, dz) = (x - self.pose.x, z - self.pose.z);
+        // the center of the circle in the coordinates of the box
+        let local = (dx * u.0 + dz * u.1, dx * v.0 + dz * v.1);
+        let half = (self.size[0] / 2.0, self.size[1] / 2.0);
+        let closest = (local.0.clamp(-half.0, half.0), local.1.clamp(-half.1, half.1));
+        let (ox, oz) = (local.0 - closest.0, local.1 - closest.1);
+        let distance = ox.hypot(oz);
+        let (normal, depth) = match distance > 1e-12 {
+            true if distance >= radius => return None,
+            true => ((ox / distance, oz / distance), radius - distance),
+            // the center is inside of the box, so leave through the nearest side
+            false => {
+                let (px, pz) = (half.0 - local.0.abs(), half.1 - local.1.abs());
+                match px < pz {
+                    true => ((local.0.signum(), 0.0), px + radius),
+                    false => ((0.0, local.1.signum()), pz + radius),
+                }
+            }
+        };
+        Some(Overlap {
+            other: self.name.clone(),
+            normal: (normal.0 * u.0 + normal.1 * v.0, normal.0 * u.1 + normal.1 * v.1),
+            depth,
+            point: (self.pose.x + closest.0 * u.0 + closest.1 * v.0, self.pose.z + closest.0 * u.1 + closest.1 * v.1),
+        })
+    }
+}
+
+// a circle that overlaps another model by `depth` meters, it has to move along
+// the unit vector `normal` to get out of it and touches it at `point`
+#[derive(Debug, Clone, PartialEq)]
+struct Overlap {
+    other: String,
+    normal: (f64, f64),
+    depth: f64,
+    point: (f64, f64),
+}
+
+pub struct World {
+    /// The extent of the world along the x and z axes, the world is centered on the origin
+    pub size: [f64; 2],
+    pub collision: Collision,
+    pub robots: Vec<Robot>,
+    pub obstacles: Vec<Obstacle>,
+    /// The simulated time in seconds
+    pub time: f64,
+    // the robots and the models they touched in the last step
+    touching: BTreeSet<(String, String)>,
+    contacts_publisher: bus::Publisher,
+}
+
+impl World {
+    /// Creates the models of the world, each robot must start out clear of
+    /// the boundary and the other models
+    pub fn new(world: &launch::World, bus: &Rc<Bus>) -> Result<World, Error> {
+        let mut robots = Vec::new();
+        let mut obstacles = Vec::new();
+        for model in world.models.iter() {
+            match model.model_type {
+                ModelType::Turtlebot => robots.push(Robot::new(model, TURTLEBOT_DRIVE, TURTLEBOT_RADIUS, world.noise, bus)?),
+                ModelType::Box => obstacles.push(Obstacle {
+                    name: model.name.clone(),
+                    pose: Pose::from_launch(&model.pose),
+                    size: model.size.unwrap_or_default(),
+                }),
+            }
+        }
+        let world = World {
+            size: world.size,
+            collision: world.collision,
+            robots,
+            obstacles,
+            time: 0.0,
+            touching: BTreeSet::new(),
+            contacts_publisher: bus.create_publisher(WORLD_NODE, CONTACTS_TOPIC, MessageType::Contact)?,
+        };
+        for (index, robot) in world.robots.iter().enumerate() {
+            if let Some(overlap) = world.overlaps(index, robot.pose.x, robot.pose.z).into_iter().next() {
+                return Err(Error::Overlap {
+                    model: robot.name.clone(),
+                    other: overlap.other,
+                });
+            }
+        }
+        Ok(world)
+    }
+
+    /// The topics that the world publishes on, which are its outputs rather
+    /// than inputs and therefore neither recorded nor replayed
+    pub fn topics(&self) -> BTreeSet<String> {
+        let robots = self.robots.iter().flat_map(|robot| {
+            let sensors = robot.sensors.iter().map(|sensor| &sensor.publisher);
+            [&robot.odometry_publisher, &robot.pose_publisher].into_iter().chain(sensors)
+        });
+        robots.chain([&self.contacts_publisher])
+            .map(|publisher| publisher.topic().to_owned())
+            .collect()
+    }
+
+    // returns the overlaps of the robot at `index` if it was at `x` and `z`
+    fn overlaps(&self, index: usize, x: f64, z: f64) -> Vec<Overlap> {
+        let radius = self.robots[index].radius;
+        let (half_width, half_depth) = (self.size[0] / 2.0, self.size[1] / 2.0);
+        let boundary = [
+            ((1.0, 0.0), x - radius + half_width, (-half_width, z)),
+            ((-1.0, 0.0), half_width - x - radius, (half_width, z)),
+            ((0.0, 1.0), z - radius + half_depth, (x, -half_depth)),
+            ((0.0, -1.0), half_depth - z - radius, (x, half_depth)),
+        ];
+        let boundary = boundary.into_iter()
+            .filter(|(_, distance, _)| *distance < 0.0)
+            .map(|(normal, distance, point)| Overlap {
+                other: BOUNDARY.to_owned(),
+                normal,
+                depth: -distance,
+                point,
+            });
+        let robots = self.robots.iter()
+            .enumerate()
+            .filter(|(other, _)| *other != index)
+            .filter_map(|(_, other)| {
+                let (dx, dz) = (x - other.pose.x, z - other.pose.z);
+                let distance = dx.hypot(dz);
+                if distance >= radius + other.radius {
+                    return None;
+                }
+                let normal = match distance > 1e-12 {
+                    true => (dx / distance, dz / distance),
+                    false => (1.0, 0.0),
+                };
+                Some(Overlap {
+                    other: other.name.clone(),
+                    normal,
+                    depth: radius + other.radius - distance,
+                    point: (other.pose.x + normal.0 * other.radius, other.pose.z + normal.1 * other.radius),
+                })
+            });
+        let obstacles = self.obstacles.iter()
+            .filter_map(|obstacle| obstacle.overlap(x, z, radius));
+        boundary.chain(robots).chain(obstacles).collect()
+    }
+
+    /// Returns the distance from `origin` along the unit vector `direction` to
+    /// the nearest boundary, robot or obstacle, ignoring the robot at `index`
+    pub fn cast(&self, index: usize, origin: (f64, f64), direction: (f64, f64)) -> f64 {
+        let (x, z) = origin;
+        let (dx, dz) = direction;
+        let (half_width, half_depth) = (self.size[0] / 2.0, self.size[1] / 2.0);
+        // the ray starts inside of the world, so it leaves through the far side of each axis
+        let boundary = [(x, dx, half_width), (z, dz, half_depth)].into_iter()
+            .filter(|(_, direction, _)| direction.abs() > 1e-12)
+            .map(|(origin, direction, half)| ((half * direction.signum() - origin) / direction).max(0.0))
+            .fold(f64::INFINITY, f64::min);
+        let robots = self.robots.iter()
+            .enumerate()
+            .filter(|(other, _)| *other != index)
+            .filter_map(|(_, robot)| {
+                // solve |origin + t * direction - center| = radius for the smaller t
+                let (ox, oz) = (x - robot.pose.x, z - robot.pose.z);
+                let b = ox * dx + oz * dz;
+                let c = ox * ox + oz * oz - robot.radius * robot.radius;
+                let discriminant = b * b - c;
+                if discriminant < 0.0 {
+                    return None;
+                }
+                let root = discriminant.sqrt();
+                match (-b - root, -b + root) {
+                    (_, far) if far < 0.0 => None,
+                    (near, _) => Some(near.max(0.0)),
+                }
+            });
+        let obstacles = self.obstacles.iter()
+            .filter_map(|obstacle| obstacle.cast(origin, direction));
+        robots.chain(obstacles).fold(boundary, f64::min)
+    }
+
+    // measures and publishes the sensors that are due at the current time
+    fn sense(&mut self) {
+        let mut measurements = Vec::new();
+        for (index, robot) in self.robots.iter().enumerate() {
+            let origin = (robot.pose.x, robot.pose.z);
+            for (sensor_index, sensor) in robot.sensors.iter().enumerate() {
+                if sensor.next_time > self.time + 1e-9 {
+                    continue;
+                }
+                let ranges: Vec<f64> = sensor.angles()
+                    .into_iter()
+                    .map(|angle| Pose { heading: robot.pose.heading + angle, ..robot.pose }.direction())
+                    .map(|direction| self.cast(index, origin, direction).min(sensor.range))
+                    .collect();
+                measurements.push((index, sensor_index, ranges));
+            }
+        }
+        for (index, sensor_index, ranges) in measurements {
+            let sensor = &mut self.robots[index].sensors[sensor_index];
+            sensor.publisher.publish(sensor.message(ranges.clone()))
+                .expect("sensors publish the message type of their topic");
+            sensor.ranges = ranges;
+            // keep the rate when steps do not line up with the period
+            sensor.next_time = (sensor.next_time + sensor.period).max(self.time);
+        }
+    }
+
+    /// Advances the simulation by `dt` seconds and returns the contacts that
+    /// started during this step, which are also published on [CONTACTS_TOPIC]
+    pub fn step(&mut self, dt: f64) -> Vec<Contact> {
+        let mut touching = BTreeSet::new();
+        let mut contacts = Vec::new();
+        for index in 0..self.robots.len() {
+            let robot = &mut self.robots[index];
+            robot.receive();
+            robot.update_odometry(dt);
+            let mut pose = robot.drive.integrate(robot.pose, robot.wheel_velocities, dt);
+            let overlaps = self.overlaps(index, pose.x, pose.z);
+            let robot = &mut self.robots[index];
+            match self.collision {
+                _ if overlaps.is_empty() => {}
+                Collision::Stop => {
+                    (pose.x, pose.z) = (robot.pose.x, robot.pose.z);
+                    robot.wheel_velocities = (0.0, 0.0);
+                }
+                Collision::Slide | Collision::Bounce => {
+                    let (linear, _) = robot.drive.velocity(robot.wheel_velocities);
+                    for overlap in overlaps.iter() {
+                        pose.x += overlap.normal.0 * overlap.depth;
+                        pose.z += overlap.normal.1 * overlap.depth;
+                        // reflect the heading if the robot is moving into the obstacle
+                        let (dx, dz) = pose.direction();
+                        let along = dx * overlap.normal.0 + dz * overlap.normal.1;
+                        if self.collision == Collision::Bounce && along * linear < 0.0 {
+                            let (dx, dz) = (dx - 2.0 * along * overlap.normal.0, dz - 2.0 * along * overlap.normal.1);
+                            pose.heading = (-dz).atan2(dx);
+                        }
+                    }
+                }
+            }
+            robot.pose = pose;
+            robot.publish_pose();
+            for overlap in overlaps {
+                let pair = (robot.name.clone(), overlap.other.clone());
+                if !self.touching.contains(&pair) {
+                    contacts.push(Contact {
+                        model: robot.name.clone(),
+                        other: overlap.other,
+                        x: overlap.point.0,
+                        z: overlap.point.1,
+                    });
+                }
+                touching.insert(pair);
+            }
+        }
+        self.touching = touching;
+        for contact in contacts.iter() {
+            self.contacts_publisher.publish(contact.clone())
+                .expect("the contacts publisher has the type of the contacts");
+        }
+        self.time += dt;
+        self.sense();
+        contacts
+    }
+}
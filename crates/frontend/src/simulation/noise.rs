@@ -0,0 +1,40 @@
+/// A small pseudorandom number generator (xorshift64*), it is seeded
+/// explicitly so that runs of the simulation can be repeated
+#[derive(Debug, Clone, PartialEq)]
+pub struct Noise {
+    state: u64,
+}
+
+impl Noise {
+    pub fn new(seed: u64) -> Noise {
+        // the state must never be zero
+        Noise { state: seed ^ 0x9e37_79b9_7f4a_7c15 | 1 }
+    }
+
+    /// Derives a seed from a name, e.g., to give each robot its own sequence
+    pub fn from_name(name: &str) -> Noise {
+        // FNV-1a
+        let seed = name.bytes()
+            .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x100_0000_01b3));
+        Noise::new(seed)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Returns a uniformly distributed number in [0, 1)
+    pub fn uniform(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns a normally distributed number using the Box-Muller transform
+    pub fn gaussian(&mut self, mean: f64, standard_deviation: f64) -> f64 {
+        let u = 1.0 - self.uniform();
+        let v = self.uniform();
+        mean + standard_deviation * (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos()
+    }
+}
@@ -0,0 +1,244 @@
+use crate::{bus::message::Contact, launch::scenario::{Assertion, Scenario}};
+
+use super::World;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Status {
+    Pending,
+    Passed { time: f64 },
+    Failed { time: f64, reason: String },
+}
+
+impl Status {
+    pub fn is_pending(&self) -> bool {
+        *self == Status::Pending
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcome {
+    pub description: String,
+    pub status: Status,
+}
+
+/// The outcomes of the assertions of a scenario at `time` seconds of simulated time
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub time: f64,
+    pub duration: f64,
+    pub outcomes: Vec<Outcome>,
+}
+
+impl Report {
+    /// Whether every assertion has either passed or failed
+    pub fn is_finished(&self) -> bool {
+        self.outcomes.iter().all(|outcome| !outcome.status.is_pending())
+    }
+
+    pub fn passed(&self) -> usize {
+        self.outcomes.iter()
+            .filter(|outcome| matches!(outcome.status, Status::Passed { .. }))
+            .count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.outcomes.iter()
+            .filter(|outcome| matches!(outcome.status, Status::Failed { .. }))
+            .count()
+    }
+
+    pub fn summary(&self) -> String {
+        format!("{} of {} assertions passed, {} failed", self.passed(), self.outcomes.len(), self.failed())
+    }
+}
+
+/// Checks the assertions of a scenario against the world after every step
+pub struct Evaluation {
+    scenario: Scenario,
+    // the closest that each robot of a reach assertion has come to its position
+    closest: Vec<f64>,
+    report: Report,
+}
+
+impl Evaluation {
+    /// Fails if an assertion refers to a model that is not a robot in `world`
+    pub fn new(scenario: Scenario, world: &World) -> Result<Evaluation, String> {
+        for assertion in scenario.assertions.iter() {
+            if let Some(model) = assertion.model() {
+                if !world.robots.iter().any(|robot| robot.name == model) {
+                    return Err(format!("the world does not contain a robot called `{model}`"));
+                }
+            }
+        }
+        let report = Report {
+            time: world.time,
+            duration: scenario.duration,
+            outcomes: scenario.assertions.iter()
+                .map(|assertion| Outcome {
+                    description: assertion.description(),
+                    status: Status::Pending,
+                })
+                .collect(),
+        };
+        Ok(Evaluation {
+            closest: vec![f64::INFINITY; scenario.assertions.len()],
+            scenario,
+            report,
+        })
+    }
+
+    /// Decides the pending assertions given the state of `world` and the
+    /// contacts that started in its last step, returns whether any outcome changed
+    pub fn update(&mut self, world: &World, contacts: &[Contact]) -> bool {
+        let time = world.time;
+        let duration = self.scenario.duration;
+        self.report.time = time;
+        let mut changed = false;
+        for (index, assertion) in self.scenario.assertions.iter().enumerate() {
+            if !self.report.outcomes[index].status.is_pending() {
+                continue;
+            }
+            let status = match assertion {
+                Assertion::Reach { model, position: [x, z], tolerance, within } => {
+                    let Some(robot) = world.robots.iter().find(|robot| robot.name == *model) else {
+                        continue;
+                    };
+                    let distance = (robot.pose.x - x).hypot(robot.pose.z - z);
+                    let closest = &mut self.closest[index];
+                    *closest = closest.min(distance);
+                    match distance <= *tolerance {
+                        true => Status::Passed { time },
+                        false if time >= *within => Status::Failed {
+                            time,
+                            reason: format!("came no closer than {:.2} m", *closest),
+                        },
+                        false => continue,
+                    }
+                }
+                Assertion::AvoidCollisions { model } => {
+                    let contact = contacts.iter()
+                        .find(|contact| model.as_ref().is_none_or(|model| contact.model == *model || contact.other == *model));
+                    match contact {
+                        Some(contact) => Status::Failed {
+                            time,
+                            reason: format!("{} collided with {} at ({:.2}, {:.2})",
+                                contact.model, contact.other, contact.x, contact.z),
+                        },
+                        None if time >= duration => Status::Passed { time },
+                        None => continue,
+                    }
+                }
+            };
+            self.report.outcomes[index].status = status;
+            changed = true;
+        }
+        changed
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.report.is_finished()
+    }
+
+    pub fn report(&self) -> &Report {
+        &self.report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::{bus::Bus, launch};
+
+    fn world(bus: &Rc<Bus>) -> World {
+        let launch = launch::parse(r#"<launch><world size="10 10">
+            <model type="turtlebot" name="a" pose="0 0 0 0 0 0" />
+            <model type="turtlebot" name="b" pose="2 2 0 0 0 0" />
+        </world></launch>"#).unwrap();
+        World::new(&launch.world, bus).unwrap()
+    }
+
+    fn evaluation(source: &str, world: &World) -> Evaluation {
+        Evaluation::new(launch::scenario::parse(source).unwrap(), world).unwrap()
+    }
+
+    fn contact(model: &str, other: &str) -> Contact {
+        Contact { model: model.to_owned(), other: other.to_owned(), x: 0.0, z: 0.0 }
+    }
+
+    #[test]
+    fn unknown_robot() {
+        let bus = Rc::new(Bus::default());
+        let world = world(&bus);
+        let scenario = launch::scenario::parse(r#"<scenario duration="5"><avoid_collisions model="c" /></scenario>"#).unwrap();
+        assert_eq!(Evaluation::new(scenario, &world).err().unwrap(), "the world does not contain a robot called `c`");
+    }
+
+    #[test]
+    fn reach_within_tolerance() {
+        let bus = Rc::new(Bus::default());
+        let mut world = world(&bus);
+        let mut evaluation = evaluation(r#"<scenario duration="5">
+            <reach model="a" position="1 0" tolerance="0.2" />
+        </scenario>"#, &world);
+        world.time = 1.0;
+        world.robots[0].pose.x = 0.7;
+        assert!(!evaluation.update(&world, &[]));
+        world.time = 2.0;
+        world.robots[0].pose.x = 0.85;
+        assert!(evaluation.update(&world, &[]));
+        assert_eq!(evaluation.report().outcomes[0].status, Status::Passed { time: 2.0 });
+        assert!(evaluation.is_finished());
+    }
+
+    #[test]
+    fn reach_fails_after_within() {
+        let bus = Rc::new(Bus::default());
+        let mut world = world(&bus);
+        let mut evaluation = evaluation(r#"<scenario duration="5">
+            <reach model="a" position="1 0" within="3" />
+        </scenario>"#, &world);
+        world.time = 1.0;
+        world.robots[0].pose.x = 0.5;
+        evaluation.update(&world, &[]);
+        world.time = 3.0;
+        world.robots[0].pose.x = 0.0;
+        assert!(evaluation.update(&world, &[]));
+        assert_eq!(evaluation.report().outcomes[0].status, Status::Failed {
+            time: 3.0,
+            reason: "came no closer than 0.50 m".to_owned(),
+        });
+    }
+
+    #[test]
+    fn avoid_collisions() {
+        let bus = Rc::new(Bus::default());
+        let mut world = world(&bus);
+        let mut evaluation = evaluation(r#"<scenario duration="5">
+            <avoid_collisions />
+            <avoid_collisions model="a" />
+            <avoid_collisions model="b" />
+        </scenario>"#, &world);
+        world.time = 1.0;
+        assert!(evaluation.update(&world, &[contact("b", "boundary")]));
+        let report = evaluation.report();
+        assert!(matches!(&report.outcomes[0].status, Status::Failed { reason, .. } if reason == "b collided with boundary at (0.00, 0.00)"));
+        assert_eq!(report.outcomes[1].status, Status::Pending);
+        assert!(matches!(report.outcomes[2].status, Status::Failed { .. }));
+        world.time = 5.0;
+        assert!(evaluation.update(&world, &[]));
+        assert_eq!(evaluation.report().outcomes[1].status, Status::Passed { time: 5.0 });
+        assert_eq!(evaluation.report().summary(), "1 of 3 assertions passed, 2 failed");
+    }
+
+    #[test]
+    fn collisions_with_other_robots() {
+        let bus = Rc::new(Bus::default());
+        let mut world = world(&bus);
+        let mut evaluation = evaluation(r#"<scenario duration="5"><avoid_collisions model="a" /></scenario>"#, &world);
+        world.time = 1.0;
+        assert!(evaluation.update(&world, &[contact("b", "a")]));
+        assert!(matches!(evaluation.report().outcomes[0].status, Status::Failed { time, .. } if time == 1.0));
+    }
+}
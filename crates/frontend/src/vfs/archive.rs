@@ -0,0 +1,121 @@
+use std::{fmt, io::{self, Cursor, Read, Write}, rc::Rc};
+
+use zip::{result::ZipError, write::FileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use super::{Directory, Entry};
+
+// the sizes in the headers of an archive can not be trusted, so at most this
+// many bytes are reserved up front for the contents of a file
+const MAX_RESERVED_BYTES: usize = 1 << 20;
+
+#[derive(Debug)]
+pub enum Error {
+    Zip(ZipError),
+    Io(io::Error),
+    Vfs(super::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Zip(error) => write!(f, "invalid archive: {error}"),
+            Error::Io(error) => write!(f, "could not read archive: {error}"),
+            Error::Vfs(error) => write!(f, "invalid archive contents: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<ZipError> for Error {
+    fn from(error: ZipError) -> Self {
+        Error::Zip(error)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<super::Error> for Error {
+    fn from(error: super::Error) -> Self {
+        Error::Vfs(error)
+    }
+}
+
+/// Writes a directory and all of its contents into a zip archive, the
+/// directory itself is the only entry at the top level of the archive
+pub fn export(directory: &Directory) -> Result<Vec<u8>, Error> {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    for entry in directory.entries() {
+        let path = entry.path.trim_start_matches('/');
+        let options = FileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .unix_permissions(entry.mode);
+        match entry.data {
+            Some(data) => {
+                writer.start_file(path, options)?;
+                writer.write_all(&data)?;
+            }
+            None => writer.add_directory(path, options)?,
+        }
+    }
+    Ok(writer.finish()?.into_inner())
+}
+
+/// Reads a zip archive into a detached directory called `root_name`. An
+/// archive that consists of a single top-level directory of that name, i.e.,
+/// one that was exported from it, is read as it is, the contents of any other
+/// archive are placed inside of a new directory of that name.
+pub fn import(data: &[u8], root_name: &str) -> Result<Rc<Directory>, Error> {
+    let mut archive = ZipArchive::new(Cursor::new(data))?;
+    let mut entries = Vec::with_capacity(archive.len());
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+        let is_dir = file.is_dir();
+        let mode = file.unix_mode()
+            .map(|mode| mode & 0o777)
+            .unwrap_or(match is_dir {
+                true => crate::DEFAULT_DIRECTORY_MODE,
+                false => crate::DEFAULT_FILE_MODE,
+            });
+        let data = match is_dir {
+            true => None,
+            false => {
+                let mut data = Vec::with_capacity((file.size() as usize).min(MAX_RESERVED_BYTES));
+                file.read_to_end(&mut data)?;
+                Some(data)
+            }
+        };
+        entries.push(Entry {
+            path: format!("/{}", file.name().trim_end_matches('/')),
+            mode,
+            data
+        });
+    }
+
+    // sorting the paths places each directory before its contents
+    entries.sort_by(|left, right| left.path.cmp(&right.path));
+    let root_path = format!("/{root_name}");
+    let exported = entries.iter().all(|entry| match entry.path.strip_prefix(&root_path) {
+        Some("") => entry.data.is_none(),
+        Some(rest) => rest.starts_with('/'),
+        None => false,
+    });
+    if !exported {
+        for entry in entries.iter_mut() {
+            entry.path.insert_str(0, &root_path);
+        }
+    }
+    let root = match entries.first() {
+        Some(Entry { path, data: None, .. }) if *path == root_path => entries.remove(0),
+        _ => Entry {
+            path: root_path,
+            mode: crate::DEFAULT_DIRECTORY_MODE,
+            data: None
+        }
+    };
+    Ok(Directory::from_entries(std::iter::once(root).chain(entries))?)
+}
@@ -0,0 +1,97 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Latin1,
+}
+
+const UTF16_LE_BOM: [u8; 2] = [0xFF, 0xFE];
+const UTF16_BE_BOM: [u8; 2] = [0xFE, 0xFF];
+
+// control characters other than tab, line feed, form feed, carriage return and
+// escape do not appear in text files, so their presence indicates binary data
+fn is_text(text: &str) -> bool {
+    !text.chars().any(|character| character.is_control() &&
+        !matches!(character, '\t' | '\n' | '\x0c' | '\r' | '\x1b') &&
+        // C1 control codes are common in Latin-1 text that was written as Windows-1252
+        !('\u{80}'..='\u{9f}').contains(&character))
+}
+
+fn utf16_units(data: &[u8], from_bytes: fn([u8; 2]) -> u16) -> Option<Vec<u16>> {
+    match data.len() % 2 {
+        0 => Some(data.chunks_exact(2).map(|unit| from_bytes([unit[0], unit[1]])).collect()),
+        _ => None,
+    }
+}
+
+impl Encoding {
+    /// Detects the encoding of `data`, returning `None` if it is not text.
+    /// UTF-16 is only detected when the data starts with a byte order mark.
+    pub fn detect(data: &[u8]) -> Option<Encoding> {
+        let candidates: &[Encoding] = match data {
+            [0xFF, 0xFE, ..] => &[Encoding::Utf16Le],
+            [0xFE, 0xFF, ..] => &[Encoding::Utf16Be],
+            _ => &[Encoding::Utf8, Encoding::Latin1],
+        };
+        candidates.iter()
+            .copied()
+            .find(|encoding| encoding.try_decode(data).is_some_and(|text| is_text(&text)))
+    }
+
+    fn try_decode(self, data: &[u8]) -> Option<String> {
+        match self {
+            Encoding::Utf8 => String::from_utf8(data.to_vec()).ok(),
+            Encoding::Utf16Le => data.strip_prefix(&UTF16_LE_BOM)
+                .and_then(|data| utf16_units(data, u16::from_le_bytes))
+                .and_then(|units| String::from_utf16(&units).ok()),
+            Encoding::Utf16Be => data.strip_prefix(&UTF16_BE_BOM)
+                .and_then(|data| utf16_units(data, u16::from_be_bytes))
+                .and_then(|units| String::from_utf16(&units).ok()),
+            Encoding::Latin1 => Some(data.iter().map(|&byte| char::from(byte)).collect()),
+        }
+    }
+
+    /// Decodes `data`, replacing anything that is not valid in this encoding
+    pub fn decode(self, data: &[u8]) -> String {
+        self.try_decode(data).unwrap_or_else(|| match self {
+            Encoding::Utf8 => String::from_utf8_lossy(data).into_owned(),
+            Encoding::Utf16Le => String::from_utf16_lossy(&data.strip_prefix(&UTF16_LE_BOM)
+                .unwrap_or(data)
+                .chunks_exact(2)
+                .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+                .collect::<Vec<_>>()),
+            Encoding::Utf16Be => String::from_utf16_lossy(&data.strip_prefix(&UTF16_BE_BOM)
+                .unwrap_or(data)
+                .chunks_exact(2)
+                .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+                .collect::<Vec<_>>()),
+            Encoding::Latin1 => unreachable!("all data is valid Latin-1"),
+        })
+    }
+
+    /// Encodes `text`, characters that can not be represented are replaced with `?`
+    pub fn encode(self, text: &str) -> Vec<u8> {
+        match self {
+            Encoding::Utf8 => text.as_bytes().to_vec(),
+            Encoding::Utf16Le => UTF16_LE_BOM.into_iter()
+                .chain(text.encode_utf16().flat_map(u16::to_le_bytes))
+                .collect(),
+            Encoding::Utf16Be => UTF16_BE_BOM.into_iter()
+                .chain(text.encode_utf16().flat_map(u16::to_be_bytes))
+                .collect(),
+            Encoding::Latin1 => text.chars()
+                .map(|character| u8::try_from(character).unwrap_or(b'?'))
+                .collect(),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf16Le => "UTF-16 LE",
+            Encoding::Utf16Be => "UTF-16 BE",
+            Encoding::Latin1 => "Latin-1",
+        }
+    }
+}
@@ -1,7 +1,15 @@
-use std::rc::Rc;
+use std::{cell::RefCell, fmt, rc::Rc};
 
+use futures::channel::mpsc;
 use futures_signals::{signal::Mutable, signal_vec::MutableVec};
 
+pub mod archive;
+pub mod encoding;
+
+thread_local! {
+    static SUBSCRIBERS: RefCell<Vec<mpsc::UnboundedSender<Event>>> = Default::default();
+}
+
 #[derive(Clone)]
 pub struct File {
     pub name: Mutable<String>,
@@ -17,3 +25,458 @@ pub struct Directory {
     pub files: MutableVec<Rc<File>>
 }
 
+#[derive(Clone)]
+pub enum Node {
+    File(Rc<File>),
+    Directory(Rc<Directory>),
+}
+
+/// A flattened file or directory, directories do not have any data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub path: String,
+    pub mode: u32,
+    pub data: Option<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    NotFound(String),
+    AlreadyExists(String),
+    NotADirectory(String),
+    IsADirectory(String),
+    InvalidName(String),
+    MovedIntoItself(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotFound(path) => write!(f, "{path}: no such file or directory"),
+            Error::AlreadyExists(path) => write!(f, "{path}: already exists"),
+            Error::NotADirectory(path) => write!(f, "{path}: not a directory"),
+            Error::IsADirectory(path) => write!(f, "{path}: is a directory"),
+            Error::InvalidName(name) => write!(f, "{name:?} is not a valid name"),
+            Error::MovedIntoItself(path) => write!(f, "{path}: can not be moved inside of itself"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventKind {
+    Created,
+    Modified,
+    Renamed { from: String },
+    Removed,
+}
+
+/// A change to the file or directory located at `path`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub path: String,
+    pub kind: EventKind,
+}
+
+/// Returns a stream of all changes made through the path-based API, the
+/// stream ends when the receiver is dropped
+pub fn subscribe() -> mpsc::UnboundedReceiver<Event> {
+    let (tx, rx) = mpsc::unbounded();
+    SUBSCRIBERS.with(|subscribers| subscribers.borrow_mut().push(tx));
+    rx
+}
+
+fn notify(path: String, kind: EventKind) {
+    let event = Event { path, kind };
+    SUBSCRIBERS.with(|subscribers| subscribers.borrow_mut()
+        .retain(|subscriber| subscriber.unbounded_send(event.clone()).is_ok()));
+}
+
+pub fn validate_name(name: &str) -> Result<(), Error> {
+    match name {
+        "" | "." | ".." => Err(Error::InvalidName(name.to_owned())),
+        _ if name.contains(['/', '\0']) => Err(Error::InvalidName(name.to_owned())),
+        _ => Ok(())
+    }
+}
+
+// splits an absolute path such as /project/velocity_control/run.py into its
+// components, the first of which is expected to be the name of the root
+fn components(path: &str) -> Vec<&str> {
+    path.split('/')
+        .filter(|component| !component.is_empty() && *component != ".")
+        .collect()
+}
+
+fn join(components: &[&str]) -> String {
+    components.iter().fold(String::new(), |path, component| path + "/" + component)
+}
+
+impl Node {
+    pub fn name(&self) -> String {
+        match self {
+            Node::File(file) => file.name.get_cloned(),
+            Node::Directory(directory) => directory.name.get_cloned(),
+        }
+    }
+
+    pub fn deep_clone(&self) -> Node {
+        match self {
+            Node::File(file) => Node::File(file.deep_clone().into()),
+            Node::Directory(directory) => Node::Directory(directory.deep_clone().into()),
+        }
+    }
+}
+
+impl File {
+    pub fn new(name: &str, mode: u32, data: Vec<u8>) -> File {
+        File {
+            name: name.to_owned().into(),
+            mode: mode.into(),
+            data: data.into(),
+        }
+    }
+
+    // deriving Clone only clones the handles to the underlying data
+    pub fn deep_clone(&self) -> File {
+        File::new(&self.name.lock_ref(), self.mode.get(), self.data.get_cloned())
+    }
+}
+
+impl Directory {
+    pub fn new(name: &str, mode: u32) -> Directory {
+        Directory {
+            name: name.to_owned().into(),
+            mode: mode.into(),
+            directories: Default::default(),
+            files: Default::default(),
+        }
+    }
+
+    // deriving Clone only clones the handles to the underlying data
+    pub fn deep_clone(&self) -> Directory {
+        Directory {
+            name: self.name.get_cloned().into(),
+            mode: self.mode.get().into(),
+            directories: self.directories.lock_ref().iter()
+                .map(|directory| Rc::new(directory.deep_clone()))
+                .collect::<Vec<_>>()
+                .into(),
+            files: self.files.lock_ref().iter()
+                .map(|file| Rc::new(file.deep_clone()))
+                .collect::<Vec<_>>()
+                .into(),
+        }
+    }
+
+    /// Flattens this directory into a list of entries where each directory
+    /// precedes its contents
+    pub fn entries(&self) -> Vec<Entry> {
+        let mut entries = Vec::new();
+        self.flatten("", &mut entries);
+        entries
+    }
+
+    fn flatten(&self, parent_path: &str, entries: &mut Vec<Entry>) {
+        let path = format!("{parent_path}/{}", self.name.lock_ref());
+        entries.push(Entry {
+            path: path.clone(),
+            mode: self.mode.get(),
+            data: None
+        });
+        for directory in self.directories.lock_ref().iter() {
+            directory.flatten(&path, entries);
+        }
+        entries.extend(self.files.lock_ref().iter().map(|file| Entry {
+            path: format!("{path}/{}", file.name.lock_ref()),
+            mode: file.mode.get(),
+            data: Some(file.data.get_cloned())
+        }));
+    }
+
+    /// Builds a detached tree from a list of entries, the first of which must
+    /// be the root directory. Missing parent directories are created with the
+    /// default mode and no change notifications are emitted.
+    pub fn from_entries(entries: impl IntoIterator<Item = Entry>) -> Result<Rc<Directory>, Error> {
+        let mut entries = entries.into_iter();
+        let root = match entries.next() {
+            Some(Entry { path, mode, data: None }) => match components(&path)[..] {
+                [name] => {
+                    validate_name(name)?;
+                    Rc::new(Directory::new(name, mode))
+                }
+                _ => return Err(Error::InvalidName(path)),
+            }
+            Some(Entry { path, .. }) => return Err(Error::NotADirectory(path)),
+            None => return Err(Error::NotFound(String::from("/"))),
+        };
+        for Entry { path, mode, data } in entries {
+            let (parent, name) = match root.lookup_parent(&path) {
+                Ok(parent) => parent,
+                Err(Error::NotFound(_)) => {
+                    let components = components(&path);
+                    // only the missing directories inside of the root are created
+                    if components[0] != *root.name.lock_ref() {
+                        return Err(Error::NotFound(path));
+                    }
+                    let mut parent = root.clone();
+                    for component in &components[1..components.len() - 1] {
+                        parent = match parent.child(component) {
+                            Some(Node::Directory(directory)) => directory,
+                            Some(Node::File(_)) => return Err(Error::NotADirectory(path)),
+                            None => {
+                                validate_name(component)?;
+                                let directory = Rc::new(Directory::new(component, crate::DEFAULT_DIRECTORY_MODE));
+                                parent.insert(Node::Directory(directory.clone()));
+                                directory
+                            }
+                        };
+                    }
+                    let name = components[components.len() - 1];
+                    validate_name(name)?;
+                    (parent, name)
+                }
+                Err(error) => return Err(error),
+            };
+            if parent.child(name).is_some() {
+                return Err(Error::AlreadyExists(path));
+            }
+            parent.insert(match data {
+                Some(data) => Node::File(File::new(name, mode, data).into()),
+                None => Node::Directory(Directory::new(name, mode).into()),
+            });
+        }
+        Ok(root)
+    }
+
+    /// Replaces the name, mode and contents of this directory with those of `other`
+    pub fn replace(self: &Rc<Self>, other: &Directory) {
+        let name = self.name.get_cloned();
+        for node in self.children() {
+            notify(format!("/{name}/{}", node.name()), EventKind::Removed);
+        }
+        self.name.set_neq(other.name.get_cloned());
+        self.mode.set_neq(other.mode.get());
+        self.directories.lock_mut().replace_cloned(other.directories.lock_ref().to_vec());
+        self.files.lock_mut().replace_cloned(other.files.lock_ref().to_vec());
+        let name = self.name.get_cloned();
+        for node in self.children() {
+            notify(format!("/{name}/{}", node.name()), EventKind::Created);
+        }
+    }
+
+    pub fn children(&self) -> Vec<Node> {
+        self.directories.lock_ref().iter()
+            .cloned()
+            .map(Node::Directory)
+            .chain(self.files.lock_ref().iter().cloned().map(Node::File))
+            .collect()
+    }
+
+    /// Returns the child with the given name, directories are checked before files
+    pub fn child(&self, name: &str) -> Option<Node> {
+        let directory = self.directories.lock_ref().iter()
+            .find(|directory| *directory.name.lock_ref() == name)
+            .cloned()
+            .map(Node::Directory);
+        directory.or_else(|| self.files.lock_ref().iter()
+            .find(|file| *file.name.lock_ref() == name)
+            .cloned()
+            .map(Node::File))
+    }
+
+    /// Resolves an absolute path, e.g., `/project/velocity_control/run.py`,
+    /// where the first component is the name of this directory
+    pub fn lookup(self: &Rc<Self>, path: &str) -> Result<Node, Error> {
+        let components = components(path);
+        match components.first() {
+            Some(root) if *root == *self.name.lock_ref() => {
+                let mut node = Node::Directory(self.clone());
+                for (index, component) in components.iter().enumerate().skip(1) {
+                    node = match node {
+                        Node::Directory(directory) => directory.child(component)
+                            .ok_or_else(|| Error::NotFound(join(&components[..=index])))?,
+                        Node::File(_) => return Err(Error::NotADirectory(join(&components[..index]))),
+                    };
+                }
+                Ok(node)
+            }
+            _ => Err(Error::NotFound(path.to_owned()))
+        }
+    }
+
+    pub fn lookup_file(self: &Rc<Self>, path: &str) -> Result<Rc<File>, Error> {
+        match self.lookup(path)? {
+            Node::File(file) => Ok(file),
+            Node::Directory(_) => Err(Error::IsADirectory(path.to_owned())),
+        }
+    }
+
+    pub fn lookup_directory(self: &Rc<Self>, path: &str) -> Result<Rc<Directory>, Error> {
+        match self.lookup(path)? {
+            Node::Directory(directory) => Ok(directory),
+            Node::File(_) => Err(Error::NotADirectory(path.to_owned())),
+        }
+    }
+
+    // resolves the directory that contains the last component of the path
+    fn lookup_parent<'a>(self: &Rc<Self>, path: &'a str) -> Result<(Rc<Directory>, &'a str), Error> {
+        let components = components(path);
+        match components.split_last() {
+            Some((name, parent)) if !parent.is_empty() => {
+                validate_name(name)?;
+                Ok((self.lookup_directory(&join(parent))?, name))
+            }
+            _ => Err(Error::InvalidName(path.to_owned()))
+        }
+    }
+
+    fn insert(&self, node: Node) {
+        match node {
+            Node::File(file) => self.files.lock_mut().push_cloned(file),
+            Node::Directory(directory) => self.directories.lock_mut().push_cloned(directory),
+        }
+    }
+
+    fn detach(&self, name: &str) -> Option<Node> {
+        let mut directories = self.directories.lock_mut();
+        if let Some(index) = directories.iter().position(|directory| *directory.name.lock_ref() == name) {
+            return Some(Node::Directory(directories.remove(index)));
+        }
+        let mut files = self.files.lock_mut();
+        files.iter()
+            .position(|file| *file.name.lock_ref() == name)
+            .map(|index| Node::File(files.remove(index)))
+    }
+
+    pub fn create_file(self: &Rc<Self>, path: &str, mode: u32, data: Vec<u8>) -> Result<Rc<File>, Error> {
+        let (parent, name) = self.lookup_parent(path)?;
+        if parent.child(name).is_some() {
+            return Err(Error::AlreadyExists(path.to_owned()));
+        }
+        let file = Rc::new(File::new(name, mode, data));
+        parent.insert(Node::File(file.clone()));
+        notify(join(&components(path)), EventKind::Created);
+        Ok(file)
+    }
+
+    pub fn create_directory(self: &Rc<Self>, path: &str, mode: u32) -> Result<Rc<Directory>, Error> {
+        let (parent, name) = self.lookup_parent(path)?;
+        if parent.child(name).is_some() {
+            return Err(Error::AlreadyExists(path.to_owned()));
+        }
+        let directory = Rc::new(Directory::new(name, mode));
+        parent.insert(Node::Directory(directory.clone()));
+        notify(join(&components(path)), EventKind::Created);
+        Ok(directory)
+    }
+
+    /// Changes the name of the file or directory at `path` in place
+    pub fn rename(self: &Rc<Self>, path: &str, name: &str) -> Result<(), Error> {
+        validate_name(name)?;
+        let (parent, current_name) = self.lookup_parent(path)?;
+        let node = parent.child(current_name)
+            .ok_or_else(|| Error::NotFound(path.to_owned()))?;
+        if current_name == name {
+            return Ok(());
+        }
+        let from = join(&components(path));
+        let to = format!("{}{name}", from.strip_suffix(current_name).unwrap_or_default());
+        if parent.child(name).is_some() {
+            return Err(Error::AlreadyExists(to));
+        }
+        match node {
+            Node::File(file) => file.name.set(name.to_owned()),
+            Node::Directory(directory) => directory.name.set(name.to_owned()),
+        }
+        notify(to, EventKind::Renamed { from });
+        Ok(())
+    }
+
+    /// Moves the file or directory at `from` so that it is located at `to`
+    pub fn move_to(self: &Rc<Self>, from: &str, to: &str) -> Result<(), Error> {
+        let (source, name) = self.lookup_parent(from)?;
+        let node = source.child(name)
+            .ok_or_else(|| Error::NotFound(from.to_owned()))?;
+        let (target, target_name) = self.lookup_parent(to)?;
+        if let Node::Directory(directory) = &node {
+            // a directory can not be moved inside of itself
+            if directory.contains_directory(&target) {
+                return Err(Error::MovedIntoItself(from.to_owned()));
+            }
+        }
+        if target.child(target_name).is_some() {
+            return Err(Error::AlreadyExists(to.to_owned()));
+        }
+        let node = source.detach(name)
+            .ok_or_else(|| Error::NotFound(from.to_owned()))?;
+        match &node {
+            Node::File(file) => file.name.set_neq(target_name.to_owned()),
+            Node::Directory(directory) => directory.name.set_neq(target_name.to_owned()),
+        }
+        target.insert(node);
+        notify(join(&components(to)), EventKind::Renamed { from: join(&components(from)) });
+        Ok(())
+    }
+
+    /// Recursively copies the file or directory at `from` to `to`
+    pub fn copy(self: &Rc<Self>, from: &str, to: &str) -> Result<Node, Error> {
+        let node = self.lookup(from)?.deep_clone();
+        let (target, target_name) = self.lookup_parent(to)?;
+        if target.child(target_name).is_some() {
+            return Err(Error::AlreadyExists(to.to_owned()));
+        }
+        match &node {
+            Node::File(file) => file.name.set(target_name.to_owned()),
+            Node::Directory(directory) => directory.name.set(target_name.to_owned()),
+        }
+        target.insert(node.clone());
+        notify(join(&components(to)), EventKind::Created);
+        Ok(node)
+    }
+
+    /// Removes the file or directory at `path` including all of its contents
+    pub fn remove(self: &Rc<Self>, path: &str) -> Result<Node, Error> {
+        let (parent, name) = self.lookup_parent(path)?;
+        let node = parent.detach(name)
+            .ok_or_else(|| Error::NotFound(path.to_owned()))?;
+        notify(join(&components(path)), EventKind::Removed);
+        Ok(node)
+    }
+
+    /// Replaces the contents of the file at `path`
+    pub fn write_file(self: &Rc<Self>, path: &str, data: Vec<u8>) -> Result<(), Error> {
+        self.lookup_file(path)?.data.set(data);
+        notify(join(&components(path)), EventKind::Modified);
+        Ok(())
+    }
+
+    /// Returns the absolute path of a file that is located inside of this directory
+    pub fn path_of(self: &Rc<Self>, file: &Rc<File>) -> Option<String> {
+        let name = self.name.get_cloned();
+        if let Some(file) = self.files.lock_ref().iter().find(|other| Rc::ptr_eq(other, file)) {
+            return Some(format!("/{name}/{}", file.name.lock_ref()));
+        }
+        self.directories.lock_ref().iter()
+            .find_map(|directory| directory.path_of(file))
+            .map(|path| format!("/{name}{path}"))
+    }
+
+    /// Returns the absolute path of a directory that is located inside of this
+    /// directory or is this directory itself
+    pub fn path_of_directory(self: &Rc<Self>, directory: &Rc<Directory>) -> Option<String> {
+        let name = self.name.get_cloned();
+        if Rc::ptr_eq(self, directory) {
+            return Some(format!("/{name}"));
+        }
+        self.directories.lock_ref().iter()
+            .find_map(|child| child.path_of_directory(directory))
+            .map(|path| format!("/{name}{path}"))
+    }
+
+    fn contains_directory(self: &Rc<Self>, other: &Rc<Directory>) -> bool {
+        Rc::ptr_eq(self, other) || self.directories.lock_ref().iter()
+            .any(|directory| directory.contains_directory(other))
+    }
+}
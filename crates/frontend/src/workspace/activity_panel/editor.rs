@@ -1,4 +1,4 @@
-use std::rc::Rc;
+use std::{cell::RefCell, rc::Rc};
 
 use codemirror_sys::{autocomplete, commands, lang_python, language, search, state, view};
 use dominator::{clone, html, stylesheet, svg, Dom};
@@ -6,6 +6,8 @@ use dominator_bulma::block;
 use futures_signals::signal::{self, Signal, SignalExt};
 use wasm_bindgen::prelude::*;
 
+use crate::vfs::encoding::Encoding;
+
 // remove this
 macro_rules! object(
     { $($key:expr => $value:expr),+ $(,)?} => {
@@ -23,16 +25,40 @@ macro_rules! object(
 
 pub struct Editor {
     pub file: Rc<crate::vfs::File>,
+    encoding: Encoding,
+    // where the file was last found in the project, which is only looked up
+    // again once the file has been moved
+    path: RefCell<Option<String>>,
 }
 
 impl Editor {
     // pass signals for saving?
-    pub fn new(file: Rc<crate::vfs::File>) -> Editor {
+    pub fn new(file: Rc<crate::vfs::File>, encoding: Encoding) -> Editor {
+        let path = crate::PROJECT.with(|project| project.path_of(&file));
         Editor {
-            file
+            file,
+            encoding,
+            path: RefCell::new(path),
         }
     }
 
+    fn save(&self, data: Vec<u8>) {
+        crate::PROJECT.with(|project| {
+            let path = self.path.borrow().clone()
+                .filter(|path| project.lookup_file(path).is_ok_and(|file| Rc::ptr_eq(&file, &self.file)))
+                .or_else(|| project.path_of(&self.file));
+            match &path {
+                Some(path) => if let Err(error) = project.write_file(path, data) {
+                    tracing::error!("could not save {path}: {error}");
+                }
+                // the changes are lost rather than written to a file outside the project
+                None => tracing::error!("could not save {}: the file has been removed from the project",
+                    self.file.name.get_cloned()),
+            }
+            *self.path.borrow_mut() = path;
+        });
+    }
+
     pub fn render(
         this: &Rc<Editor>,
         width: impl Signal<Item = u32> + 'static,
@@ -47,15 +73,14 @@ impl Editor {
         let update_closure = clone!(this => move |update: view::ViewUpdate| {
             if update.doc_changed() {
                 // autosave
-                let data = update.state().doc().to_string().as_bytes().to_vec();
-                this.file.data.replace(data);
+                this.save(this.encoding.encode(&update.state().doc().to_string()));
             }
         });
 
         // TODO: this is not necessary for the moment, but when opening the
         // file, we are just taking a single snapshot and not updating it.
         // This is ok since we only allow one editor per file.
-        let data = String::from_utf8(this.file.data.get_cloned()).unwrap();
+        let data = this.encoding.decode(&this.file.data.lock_ref());
     
         let language = state::Compartment::new();
         let state = state::EditorState::create(&object! {
@@ -106,6 +131,7 @@ impl Editor {
     // this should also be turned into some sort of signal
     pub fn label(&self) -> Dom {
         html!("span", {
+            .attr("title", self.encoding.label())
             .text_signal(self.file.name.signal_cloned())
         })
     }
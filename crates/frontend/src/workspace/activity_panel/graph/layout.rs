@@ -0,0 +1,179 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::bus::TopicInfo;
+
+// dimensions in pixels, text is assumed to be about this wide per character
+const CHARACTER_WIDTH: f64 = 7.0;
+const BOX_PADDING: f64 = 12.0;
+pub const BOX_HEIGHT: f64 = 28.0;
+const ROW_GAP: f64 = 28.0;
+// leaves room for the labels of the edges between columns
+const COLUMN_GAP: f64 = 160.0;
+const MARGIN: f64 = 24.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Kind {
+    Node,
+    /// A topic that is missing either its publishers or its subscriptions,
+    /// these are shown on their own so that they stand out
+    Topic,
+}
+
+/// A node or topic placed at `x`, `y`, which is its top left corner
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vertex {
+    pub name: String,
+    pub kind: Kind,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+}
+
+/// The topics that connect two vertices, which are indices into [Layout::vertices]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    pub topics: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Layout {
+    pub vertices: Vec<Vertex>,
+    pub edges: Vec<Edge>,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Arranges the nodes in columns so that messages mostly flow from left to
+/// right, nodes that only publish are in the first column
+pub fn layout(topics: &[TopicInfo]) -> Layout {
+    // the vertices in order of their names and the edges between them
+    let mut names = BTreeSet::new();
+    let mut connections = BTreeMap::<((Kind, String), (Kind, String)), Vec<String>>::new();
+    for topic in topics {
+        let publishers = topic.publishers.iter()
+            .map(|endpoint| (Kind::Node, endpoint.node.clone()))
+            .collect::<BTreeSet<_>>();
+        let subscriptions = topic.subscriptions.iter()
+            .map(|endpoint| (Kind::Node, endpoint.node.clone()))
+            .collect::<BTreeSet<_>>();
+        names.extend(publishers.iter().cloned());
+        names.extend(subscriptions.iter().cloned());
+        let vertex = (Kind::Topic, topic.name.clone());
+        let pairs: Vec<_> = match (publishers.is_empty(), subscriptions.is_empty()) {
+            (false, true) => {
+                names.insert(vertex.clone());
+                publishers.into_iter().map(|publisher| (publisher, vertex.clone())).collect()
+            }
+            (true, false) => {
+                names.insert(vertex.clone());
+                subscriptions.into_iter().map(|subscription| (vertex.clone(), subscription)).collect()
+            }
+            _ => publishers.iter()
+                .flat_map(|publisher| subscriptions.iter().map(|subscription| (publisher.clone(), subscription.clone())))
+                .collect(),
+        };
+        for pair in pairs {
+            connections.entry(pair).or_default().push(topic.name.clone());
+        }
+    }
+    let names = names.into_iter().collect::<Vec<_>>();
+    let index = |vertex: &(Kind, String)| names.binary_search(vertex).unwrap();
+    let edges = connections.into_iter()
+        .map(|((from, to), topics)| Edge {
+            from: index(&from),
+            to: index(&to),
+            topics,
+        })
+        .collect::<Vec<_>>();
+
+    // the column of each vertex is the length of the longest path leading to
+    // it, ignoring the edges that close cycles
+    let forward = forward_edges(names.len(), &edges);
+    let mut columns = vec![0; names.len()];
+    for _ in 0..names.len() {
+        let mut changed = false;
+        for edge in forward.iter().map(|index| &edges[*index]) {
+            if columns[edge.to] <= columns[edge.from] {
+                columns[edge.to] = columns[edge.from] + 1;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let widths = names.iter()
+        .map(|(_, name)| name.chars().count() as f64 * CHARACTER_WIDTH + 2.0 * BOX_PADDING)
+        .collect::<Vec<_>>();
+    let column_count = columns.iter().max().map_or(0, |column| column + 1);
+    let mut column_widths = vec![0.0_f64; column_count];
+    let mut rows = vec![0; column_count];
+    let mut vertices = Vec::with_capacity(names.len());
+    let mut row_of = Vec::with_capacity(names.len());
+    for (index, column) in columns.iter().enumerate() {
+        column_widths[*column] = column_widths[*column].max(widths[index]);
+        row_of.push(rows[*column]);
+        rows[*column] += 1;
+    }
+    let column_x = column_widths.iter()
+        .scan(MARGIN, |x, width| {
+            let column_x = *x;
+            *x += width + COLUMN_GAP;
+            Some(column_x)
+        })
+        .collect::<Vec<_>>();
+    for (index, (kind, name)) in names.into_iter().enumerate() {
+        let column = columns[index];
+        vertices.push(Vertex {
+            name,
+            kind,
+            // centered within the column
+            x: column_x[column] + (column_widths[column] - widths[index]) / 2.0,
+            y: MARGIN + row_of[index] as f64 * (BOX_HEIGHT + ROW_GAP),
+            width: widths[index],
+        });
+    }
+    let width = column_x.last().zip(column_widths.last()).map_or(0.0, |(x, width)| x + width + MARGIN);
+    let height = rows.iter().max().map_or(0.0, |rows| MARGIN + *rows as f64 * (BOX_HEIGHT + ROW_GAP) - ROW_GAP + MARGIN);
+    Layout { vertices, edges, width, height }
+}
+
+// returns the indices of the edges that remain after removing self loops and
+// the edges that lead back to a vertex on the current path of a depth first
+// search, which starts from the vertices without incoming edges
+fn forward_edges(count: usize, edges: &[Edge]) -> Vec<usize> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        Unvisited,
+        OnPath,
+        Done,
+    }
+
+    fn visit(vertex: usize, edges: &[Edge], states: &mut [State], forward: &mut Vec<usize>) {
+        states[vertex] = State::OnPath;
+        for (index, edge) in edges.iter().enumerate().filter(|(_, edge)| edge.from == vertex) {
+            match states[edge.to] {
+                State::Unvisited => {
+                    forward.push(index);
+                    visit(edge.to, edges, states, forward);
+                }
+                State::Done => forward.push(index),
+                State::OnPath => {}
+            }
+        }
+        states[vertex] = State::Done;
+    }
+
+    let mut states = vec![State::Unvisited; count];
+    let mut forward = Vec::new();
+    let roots = (0..count).filter(|vertex| !edges.iter().any(|edge| edge.to == *vertex && edge.from != *vertex));
+    for vertex in roots.chain(0..count) {
+        if states[vertex] == State::Unvisited {
+            visit(vertex, edges, &mut states, &mut forward);
+        }
+    }
+    forward
+}
@@ -0,0 +1,191 @@
+use std::rc::Rc;
+
+use dominator::{html, svg, Dom};
+use dominator_bulma::block;
+use futures::StreamExt;
+use futures_signals::signal::{self, Mutable, Signal, SignalExt};
+use gloo_timers::future::IntervalStream;
+
+use crate::bus::TopicInfo;
+
+mod layout;
+
+use layout::{Kind, Layout, BOX_HEIGHT};
+
+// the interval between checks for new publishers and subscriptions
+const REFRESH_MS: u32 = 500;
+const ARROW_ID: &str = "graph-arrow";
+const NODE_COLOR: &str = "#485fc7";
+const EDGE_COLOR: &str = "#7a7a7a";
+// topics without publishers or subscriptions are often misspelled
+const WARNING_COLOR: &str = "#f14668";
+
+pub(crate) const ICON_PATH: &str = "M9,2V8H11V11H5C3.89,11 3,11.89 3,13V16H1V22H7V16H5V13H11V16H9V22H15V16H13V13H19V\
+    16H17V22H23V16H21V13C21,11.89 20.11,11 19,11H13V8H15V2H9Z";
+
+/// Draws the nodes on the bus as boxes connected by the topics between them
+pub struct Graph {
+    topics: Mutable<Vec<TopicInfo>>,
+    layout: Mutable<Rc<Layout>>,
+}
+
+impl Graph {
+    pub fn new() -> Graph {
+        Graph {
+            topics: Default::default(),
+            layout: Default::default(),
+        }
+    }
+
+    // the layout only changes when publishers or subscriptions come and go
+    async fn refresh(this: Rc<Graph>) {
+        let bus = crate::BUS.with(|bus| Rc::clone(bus));
+        let mut interval = IntervalStream::new(REFRESH_MS);
+        loop {
+            let topics = bus.topics();
+            if *this.topics.lock_ref() != topics {
+                this.layout.set(Rc::new(layout::layout(&topics)));
+                this.topics.set(topics);
+            }
+            if interval.next().await.is_none() {
+                break;
+            }
+        }
+    }
+
+    fn render_graph(layout: &Layout) -> Dom {
+        let arrow = svg!("defs", {
+            .child(svg!("marker", {
+                .attr("id", ARROW_ID)
+                .attr("viewBox", "0 0 10 10")
+                .attr("refX", "10")
+                .attr("refY", "5")
+                .attr("markerWidth", "8")
+                .attr("markerHeight", "8")
+                .attr("orient", "auto-start-reverse")
+                .child(svg!("path", {
+                    .attr("d", "M0,0L10,5L0,10Z")
+                    .attr("fill", EDGE_COLOR)
+                }))
+            }))
+        });
+        let edges = layout.edges.iter().map(|edge| {
+            let (from, to) = (&layout.vertices[edge.from], &layout.vertices[edge.to]);
+            let dangling = from.kind == Kind::Topic || to.kind == Kind::Topic;
+            let (x1, y1) = (from.x + from.width, from.y + BOX_HEIGHT / 2.0);
+            let (x2, y2) = (to.x, to.y + BOX_HEIGHT / 2.0);
+            // a cubic curve that leaves and enters horizontally, nodes that
+            // subscribe to their own topics get a loop above them
+            let (path, label) = match edge.from == edge.to {
+                true => (
+                    format!("M{},{} C{},{} {},{} {},{}", x1 - 8.0, from.y, x1 + 24.0, from.y - 36.0,
+                        from.x - 24.0, from.y - 36.0, from.x + 8.0, from.y),
+                    ((from.x + x1) / 2.0, from.y - 32.0),
+                ),
+                false => {
+                    let bend = ((x2 - x1).abs() / 2.0).max(40.0);
+                    (
+                        format!("M{x1},{y1} C{},{y1} {},{y2} {x2},{y2}", x1 + bend, x2 - bend),
+                        ((x1 + x2) / 2.0, (y1 + y2) / 2.0 - 6.0),
+                    )
+                }
+            };
+            svg!("g", {
+                .child(svg!("path", {
+                    .attr("d", &path)
+                    .attr("fill", "none")
+                    .attr("stroke", if dangling { WARNING_COLOR } else { EDGE_COLOR })
+                    .attr("stroke-width", "1.5")
+                    .attr("marker-end", &format!("url(#{ARROW_ID})"))
+                }))
+                .apply_if(!dangling, |dom| dom.child(svg!("text", {
+                    .attr("x", &label.0.to_string())
+                    .attr("y", &label.1.to_string())
+                    .attr("text-anchor", "middle")
+                    .attr("font-size", "11")
+                    .attr("font-family", "monospace")
+                    .text(&edge.topics.join(", "))
+                })))
+            })
+        });
+        let vertices = layout.vertices.iter().map(|vertex| {
+            let (color, radius, title) = match vertex.kind {
+                Kind::Node => (NODE_COLOR, "4", "Node"),
+                Kind::Topic => (WARNING_COLOR, "14", "Topic without publishers or subscriptions, is its name spelled correctly?"),
+            };
+            svg!("g", {
+                .child(svg!("title", {
+                    .text(title)
+                }))
+                .child(svg!("rect", {
+                    .attr("x", &vertex.x.to_string())
+                    .attr("y", &vertex.y.to_string())
+                    .attr("width", &vertex.width.to_string())
+                    .attr("height", &BOX_HEIGHT.to_string())
+                    .attr("rx", radius)
+                    .attr("fill", "white")
+                    .attr("stroke", color)
+                    .attr("stroke-width", "1.5")
+                    .apply_if(vertex.kind == Kind::Topic, |dom| dom.attr("stroke-dasharray", "4 3"))
+                }))
+                .child(svg!("text", {
+                    .attr("x", &(vertex.x + vertex.width / 2.0).to_string())
+                    .attr("y", &(vertex.y + BOX_HEIGHT / 2.0).to_string())
+                    .attr("text-anchor", "middle")
+                    .attr("dominant-baseline", "central")
+                    .attr("font-size", "12")
+                    .attr("font-family", "monospace")
+                    .attr("fill", color)
+                    .text(&vertex.name)
+                }))
+            })
+        });
+        svg!("svg", {
+            .attr("width", &layout.width.to_string())
+            .attr("height", &layout.height.to_string())
+            .child(arrow)
+            .children(edges)
+            .children(vertices)
+        })
+    }
+
+    pub fn render(
+        this: &Rc<Graph>,
+        width: impl Signal<Item = u32> + 'static,
+        height: impl Signal<Item = u32> + 'static
+    ) -> impl Signal<Item = Option<Dom>> {
+        let dom = block!({
+            .class("has-background-white")
+            .style("overflow", "auto")
+            .style_signal("height", height.map(|height| format!("{height}px")))
+            .style_signal("width", width.map(|width| format!("{width}px")))
+            .future(Self::refresh(this.clone()))
+            .child_signal(this.layout.signal_cloned().map(|layout| Some(match layout.vertices.is_empty() {
+                true => html!("p", {
+                    .class("p-3")
+                    .class("is-size-7")
+                    .class("has-text-grey")
+                    .text("Nothing is connected to the bus, start the simulator to see its nodes and topics")
+                }),
+                false => Self::render_graph(&layout),
+            })))
+        });
+        signal::always(Some(dom))
+    }
+
+    pub fn label(&self) -> Dom {
+        html!("span", {
+            .text("Graph")
+        })
+    }
+
+    pub fn icon(&self) -> Dom {
+        svg!("svg", {
+            .attr("height", "1.25em")
+            .attr("viewBox", "0 0 24 24")
+            .child(svg!("path", {
+                .attr("d", ICON_PATH)
+            }))
+        })
+    }
+}
@@ -0,0 +1,85 @@
+use std::{fmt::Write, rc::Rc};
+
+use dominator::{html, svg, Dom};
+use dominator_bulma::block;
+use futures_signals::signal::{self, Signal, SignalExt};
+
+const BYTES_PER_LINE: usize = 16;
+// larger files are truncated to keep the page responsive
+const MAX_BYTES: usize = 1 << 20;
+
+fn dump(data: &[u8]) -> String {
+    let mut dump = String::new();
+    for (line, bytes) in data[..data.len().min(MAX_BYTES)].chunks(BYTES_PER_LINE).enumerate() {
+        write!(dump, "{:08x}  ", line * BYTES_PER_LINE).unwrap();
+        for index in 0..BYTES_PER_LINE {
+            match bytes.get(index) {
+                Some(byte) => write!(dump, "{byte:02x} ").unwrap(),
+                None => dump.push_str("   "),
+            }
+            if index == BYTES_PER_LINE / 2 - 1 {
+                dump.push(' ');
+            }
+        }
+        dump.push_str(" |");
+        dump.extend(bytes.iter().map(|&byte| match byte.is_ascii_graphic() || byte == b' ' {
+            true => char::from(byte),
+            false => '.',
+        }));
+        dump.push_str("|\n");
+    }
+    if data.len() > MAX_BYTES {
+        writeln!(dump, "... {} more bytes", data.len() - MAX_BYTES).unwrap();
+    }
+    dump
+}
+
+/// A read-only view of files that could not be decoded as text
+pub struct HexViewer {
+    pub file: Rc<crate::vfs::File>,
+}
+
+impl HexViewer {
+    pub fn new(file: Rc<crate::vfs::File>) -> HexViewer {
+        HexViewer {
+            file
+        }
+    }
+
+    pub fn render(
+        this: &Rc<HexViewer>,
+        width: impl Signal<Item = u32> + 'static,
+        height: impl Signal<Item = u32> + 'static
+    ) -> impl Signal<Item = Option<Dom>> {
+        let dom = block!({
+            .style("overflow", "scroll")
+            .style_signal("height", height.map(|height| format!("{height}px")))
+            .style_signal("width", width.map(|width| format!("{width}px")))
+            .child(html!("pre", {
+                .class("is-size-7")
+                .class("has-background-white")
+                .text_signal(this.file.data.signal_ref(|data| dump(data)))
+            }))
+        });
+        signal::always(Some(dom))
+    }
+
+    pub fn label(&self) -> Dom {
+        html!("span", {
+            .attr("title", "Binary file (read-only)")
+            .text_signal(self.file.name.signal_cloned())
+        })
+    }
+
+    pub fn icon(&self) -> Dom {
+        const PATH: &str = "M14,2H6A2,2 0 0,0 4,4V20A2,2 0 0,0 6,22H18A2,2 0 0,0 20,20V8L14,2M13,\
+            9V3.5L18.5,9H13M7,12H9V18H7V12M10,12H14V14H12V16H14V18H10V12M15,12H17V18H15V12Z";
+        svg!("svg", {
+            .attr("height", "1.25em")
+            .attr("viewBox", "0 0 24 24")
+            .child(svg!("path", {
+                .attr("d", PATH)
+            }))
+        })
+    }
+}
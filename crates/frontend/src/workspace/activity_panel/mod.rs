@@ -4,9 +4,14 @@ use dominator::{clone, events, html, svg, Dom, EventOptions};
 use dominator_bulma::{block, column, columns, icon, icon_text};
 use futures::StreamExt;
 use futures_signals::{signal::{self, Mutable, Signal, SignalExt}, signal_vec::{MutableVec, SignalVecExt}};
-use crate::contextmenu::ContextMenuState;
+use crate::{contextmenu::ContextMenuState, vfs::encoding::Encoding};
 
 pub mod editor;
+pub mod graph;
+pub mod hex_viewer;
+pub mod plot;
+pub mod results;
+pub mod simulator;
 pub mod welcome;
 
 const TAB_HEIGHT: u32 = 48;
@@ -14,6 +19,11 @@ const TAB_HEIGHT: u32 = 48;
 #[derive(Clone)]
 enum Activity {
     Editor(Rc<editor::Editor>),
+    Graph(Rc<graph::Graph>),
+    HexViewer(Rc<hex_viewer::HexViewer>),
+    Plot(Rc<plot::Plot>),
+    Results(Rc<results::Results>),
+    Simulator(Rc<simulator::Simulator>),
     Welcome(Rc<welcome::Welcome>),
 }
 
@@ -25,6 +35,11 @@ impl Activity {
     ) -> Pin<Box<dyn Signal<Item = Option<dominator::Dom>>>> {
         match this.as_ref() {
             Activity::Editor(editor) => Box::pin(editor::Editor::render(editor, width, height)),
+            Activity::Graph(graph) => Box::pin(graph::Graph::render(graph, width, height)),
+            Activity::HexViewer(hex_viewer) => Box::pin(hex_viewer::HexViewer::render(hex_viewer, width, height)),
+            Activity::Plot(plot) => Box::pin(plot::Plot::render(plot, width, height)),
+            Activity::Results(results) => Box::pin(results::Results::render(results, width, height)),
+            Activity::Simulator(simulator) => Box::pin(simulator::Simulator::render(simulator, width, height)),
             Activity::Welcome(welcome) => Box::pin(welcome::Welcome::render(welcome, width, height)),
         }
     }
@@ -32,6 +47,11 @@ impl Activity {
     pub fn label(&self) -> Dom {
         match self {
             Activity::Editor(editor) => editor.label(),
+            Activity::Graph(graph) => graph.label(),
+            Activity::HexViewer(hex_viewer) => hex_viewer.label(),
+            Activity::Plot(plot) => plot.label(),
+            Activity::Results(results) => results.label(),
+            Activity::Simulator(simulator) => simulator.label(),
             Activity::Welcome(welcome) => welcome.label(),
         }
     }
@@ -39,6 +59,11 @@ impl Activity {
     pub fn icon(&self) -> Dom {
         match self {
             Activity::Editor(editor) => editor.icon(),
+            Activity::Graph(graph) => graph.icon(),
+            Activity::HexViewer(hex_viewer) => hex_viewer.icon(),
+            Activity::Plot(plot) => plot.icon(),
+            Activity::Results(results) => results.icon(),
+            Activity::Simulator(simulator) => simulator.icon(),
             Activity::Welcome(welcome) => welcome.icon(),
         }
     }
@@ -79,7 +104,7 @@ impl Activity {
                 }))
             }))
             .child(this.label())
-            .apply_if(matches!(**this, Activity::Editor(_)), |dom| {
+            .apply_if(matches!(**this, Activity::Editor(_) | Activity::Graph(_) | Activity::HexViewer(_) | Activity::Plot(_) | Activity::Results(_) | Activity::Simulator(_)), |dom| {
                 dom.child(icon!({
                     .event(clone!(mouse_over_close => move |_: events::PointerOver| {
                         mouse_over_close.set_neq(true);
@@ -144,6 +169,11 @@ impl ActivityPanel {
     fn split_tab(&self, activity: Rc<Activity>) {
         let new_activity = match &*activity {
             Activity::Editor(editor) => Activity::Editor(editor.clone()),
+            Activity::Graph(graph) => Activity::Graph(graph.clone()),
+            Activity::HexViewer(hex_viewer) => Activity::HexViewer(hex_viewer.clone()),
+            Activity::Plot(plot) => Activity::Plot(plot.clone()),
+            Activity::Results(results) => Activity::Results(results.clone()),
+            Activity::Simulator(simulator) => Activity::Simulator(simulator.clone()),
             Activity::Welcome(welcome) => Activity::Welcome(Rc::new(welcome::Welcome::new())),
         };
         self.activities.lock_mut().push_cloned(Rc::new(new_activity));
@@ -165,18 +195,72 @@ impl ActivityPanel {
                 match command {
                     crate::WorkspaceCommand::OpenFile(file) => {
                         let mut activities = this.activities.lock_mut();
-                        let editor = activities.iter()
+                        let activity = activities.iter()
                             .find(|activity| match &***activity {
                                 Activity::Editor(editor) => Rc::ptr_eq(&editor.file, &file),
+                                Activity::HexViewer(hex_viewer) => Rc::ptr_eq(&hex_viewer.file, &file),
                                 _ => false,
                             })
                             .cloned()
                             .unwrap_or_else(move || {
-                                let editor = Rc::new(Activity::Editor(Rc::new(editor::Editor::new(file))));
-                                activities.push_cloned(editor.clone());
-                                editor
+                                // files that can not be decoded as text are opened read-only
+                                let encoding = Encoding::detect(&file.data.lock_ref());
+                                let activity = Rc::new(match encoding {
+                                    Some(encoding) => Activity::Editor(Rc::new(editor::Editor::new(file, encoding))),
+                                    None => Activity::HexViewer(Rc::new(hex_viewer::HexViewer::new(file))),
+                                });
+                                activities.push_cloned(activity.clone());
+                                activity
                             });
-                        this.active_activity.set(Some(editor));
+                        this.active_activity.set(Some(activity));
+                    }
+                    crate::WorkspaceCommand::OpenSimulator => {
+                        let mut activities = this.activities.lock_mut();
+                        let simulator = activities.iter()
+                            .find(|activity| matches!(***activity, Activity::Simulator(_)))
+                            .cloned()
+                            .unwrap_or_else(move || {
+                                let simulator = Rc::new(Activity::Simulator(Rc::new(simulator::Simulator::new())));
+                                activities.push_cloned(simulator.clone());
+                                simulator
+                            });
+                        this.active_activity.set(Some(simulator));
+                    }
+                    crate::WorkspaceCommand::OpenGraph => {
+                        let mut activities = this.activities.lock_mut();
+                        let graph = activities.iter()
+                            .find(|activity| matches!(***activity, Activity::Graph(_)))
+                            .cloned()
+                            .unwrap_or_else(move || {
+                                let graph = Rc::new(Activity::Graph(Rc::new(graph::Graph::new())));
+                                activities.push_cloned(graph.clone());
+                                graph
+                            });
+                        this.active_activity.set(Some(graph));
+                    }
+                    crate::WorkspaceCommand::OpenPlot => {
+                        let mut activities = this.activities.lock_mut();
+                        let plot = activities.iter()
+                            .find(|activity| matches!(***activity, Activity::Plot(_)))
+                            .cloned()
+                            .unwrap_or_else(move || {
+                                let plot = Rc::new(Activity::Plot(Rc::new(plot::Plot::new())));
+                                activities.push_cloned(plot.clone());
+                                plot
+                            });
+                        this.active_activity.set(Some(plot));
+                    }
+                    crate::WorkspaceCommand::OpenResults => {
+                        let mut activities = this.activities.lock_mut();
+                        let results = activities.iter()
+                            .find(|activity| matches!(***activity, Activity::Results(_)))
+                            .cloned()
+                            .unwrap_or_else(move || {
+                                let results = Rc::new(Activity::Results(Rc::new(results::Results::new())));
+                                activities.push_cloned(results.clone());
+                                results
+                            });
+                        this.active_activity.set(Some(results));
                     }
                 }
             }))))
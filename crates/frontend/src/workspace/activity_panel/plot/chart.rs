@@ -0,0 +1,139 @@
+use std::collections::VecDeque;
+
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+
+// space in pixels around the plotting area for the labels of the axes
+const MARGIN_LEFT: f64 = 56.0;
+const MARGIN_RIGHT: f64 = 16.0;
+const MARGIN_TOP: f64 = 16.0;
+const MARGIN_BOTTOM: f64 = 28.0;
+// ticks are chosen so that there are about this many along each axis
+const TICKS: f64 = 6.0;
+
+const BACKGROUND_COLOR: &str = "#ffffff";
+const GRID_COLOR: &str = "#ededed";
+const AXIS_COLOR: &str = "#b5b5b5";
+const TEXT_COLOR: &str = "#4a4a4a";
+const FONT: &str = "11px sans-serif";
+pub const SERIES_COLORS: [&str; 6] = ["#485fc7", "#f14668", "#48c78e", "#ffb70f", "#3e8ed0", "#b86bff"];
+
+/// The values of a field over time, `points` holds the time in seconds and the value
+pub struct Series<'a> {
+    pub label: &'a str,
+    pub color: &'a str,
+    pub points: &'a VecDeque<(f64, f64)>,
+}
+
+// the largest step of 1, 2 or 5 times a power of ten that gives at least `TICKS` ticks
+fn tick_step(range: f64) -> f64 {
+    let rough = range / TICKS;
+    let magnitude = 10_f64.powf(rough.log10().floor());
+    [1.0, 2.0, 5.0, 10.0].into_iter()
+        .map(|factor| factor * magnitude)
+        .find(|step| *step >= rough)
+        .unwrap_or(10.0 * magnitude)
+}
+
+fn ticks(min: f64, max: f64) -> impl Iterator<Item = f64> {
+    let step = tick_step(max - min);
+    ((min / step).ceil() as i64..=(max / step).floor() as i64).map(move |index| index as f64 * step)
+}
+
+fn context(canvas: &HtmlCanvasElement) -> CanvasRenderingContext2d {
+    canvas.get_context("2d")
+        .unwrap()
+        .unwrap()
+        .unchecked_into()
+}
+
+/// Draws the series between `end - window` and `end` seconds onto a canvas of
+/// `width` by `height` CSS pixels, the vertical axis fits the visible values
+pub fn draw(canvas: &HtmlCanvasElement, width: u32, height: u32, series: &[Series], end: f64, window: f64) {
+    let context = context(canvas);
+    let ratio = f64::from(canvas.width()) / f64::from(width.max(1));
+    let (width, height) = (f64::from(width), f64::from(height));
+    context.set_transform(ratio, 0.0, 0.0, ratio, 0.0, 0.0).unwrap();
+    context.set_fill_style(&JsValue::from_str(BACKGROUND_COLOR));
+    context.fill_rect(0.0, 0.0, width, height);
+
+    let start = end - window;
+    let (mut min, mut max) = series.iter()
+        .flat_map(|series| series.points.iter())
+        .filter(|(time, _)| *time >= start && *time <= end)
+        .map(|(_, value)| *value)
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| (min.min(value), max.max(value)));
+    if min > max {
+        (min, max) = (-1.0, 1.0);
+    }
+    let padding = match max - min > 1e-9 {
+        true => (max - min) * 0.1,
+        false => 1.0,
+    };
+    let (min, max) = (min - padding, max + padding);
+
+    let (left, right) = (MARGIN_LEFT, (width - MARGIN_RIGHT).max(MARGIN_LEFT + 1.0));
+    let (top, bottom) = (MARGIN_TOP, (height - MARGIN_BOTTOM).max(MARGIN_TOP + 1.0));
+    let x = |time: f64| left + (time - start) / window * (right - left);
+    let y = |value: f64| bottom - (value - min) / (max - min) * (bottom - top);
+
+    context.set_line_width(1.0);
+    context.set_font(FONT);
+    context.set_fill_style(&JsValue::from_str(TEXT_COLOR));
+    context.set_stroke_style(&JsValue::from_str(GRID_COLOR));
+    context.set_text_align("center");
+    context.set_text_baseline("top");
+    for time in ticks(start, end) {
+        context.begin_path();
+        context.move_to(x(time), top);
+        context.line_to(x(time), bottom);
+        context.stroke();
+        context.fill_text(&format!("{time:.1}"), x(time), bottom + 4.0).unwrap();
+    }
+    context.set_text_align("right");
+    context.set_text_baseline("middle");
+    let step = tick_step(max - min);
+    let decimals = (-step.log10().floor()).max(0.0) as usize;
+    for value in ticks(min, max) {
+        context.begin_path();
+        context.move_to(left, y(value));
+        context.line_to(right, y(value));
+        context.stroke();
+        context.fill_text(&format!("{value:.decimals$}"), left - 4.0, y(value)).unwrap();
+    }
+    context.set_text_align("right");
+    context.set_text_baseline("bottom");
+    context.fill_text("t [s]", right, height - 2.0).unwrap();
+    context.set_stroke_style(&JsValue::from_str(AXIS_COLOR));
+    context.stroke_rect(left, top, right - left, bottom - top);
+
+    // lines are clipped to the plotting area since they start before the window
+    context.save();
+    context.begin_path();
+    context.rect(left, top, right - left, bottom - top);
+    context.clip();
+    context.set_line_width(1.5);
+    for series in series {
+        context.set_stroke_style(&JsValue::from_str(series.color));
+        context.begin_path();
+        for (index, (time, value)) in series.points.iter().enumerate() {
+            match index {
+                0 => context.move_to(x(*time), y(*value)),
+                _ => context.line_to(x(*time), y(*value)),
+            }
+        }
+        context.stroke();
+    }
+    context.restore();
+
+    // the legend
+    context.set_text_align("left");
+    context.set_text_baseline("middle");
+    for (index, series) in series.iter().enumerate() {
+        let row = top + 10.0 + index as f64 * 16.0;
+        context.set_fill_style(&JsValue::from_str(series.color));
+        context.fill_rect(left + 8.0, row - 1.5, 12.0, 3.0);
+        context.set_fill_style(&JsValue::from_str(TEXT_COLOR));
+        context.fill_text(series.label, left + 24.0, row).unwrap();
+    }
+}
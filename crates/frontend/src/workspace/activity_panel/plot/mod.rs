@@ -0,0 +1,312 @@
+use std::{cell::RefCell, collections::VecDeque, rc::Rc};
+
+use dominator::{clone, events, html, svg, Dom};
+use dominator_bulma::block;
+use futures::StreamExt;
+use futures_signals::{map_ref, signal::{self, Mutable, Signal, SignalExt}, signal_vec::{MutableVec, SignalVecExt}};
+use gloo_timers::future::IntervalStream;
+use web_sys::{HtmlCanvasElement, HtmlSelectElement};
+
+use crate::bus::TopicInfo;
+
+use super::simulator::canvas;
+
+mod chart;
+
+// the interval between reading the messages from the bus in wall time
+const UPDATE_MS: u32 = 50;
+// the number of updates between checks for new topics
+const REFRESH_UPDATES: u32 = 10;
+const TOOLBAR_HEIGHT: u32 = 48;
+// the duration of simulated time that is visible
+const WINDOW_SECONDS: f64 = 10.0;
+
+pub(crate) const ICON_PATH: &str = "M16,11.78L20.24,4.45L21.97,5.45L16.74,14.5L10.23,10.75L5.46,19H22V21H2V3H4V\
+    17.54L9.5,8L16,11.78Z";
+
+// the values of a field of the messages on a topic
+struct Series {
+    topic: String,
+    field: String,
+    color: &'static str,
+    points: RefCell<VecDeque<(f64, f64)>>,
+}
+
+impl Series {
+    fn label(&self) -> String {
+        format!("{}.{}", self.topic, self.field)
+    }
+
+    fn push(&self, time: f64, value: f64) {
+        let mut points = self.points.borrow_mut();
+        // the simulation was restarted
+        if points.back().is_some_and(|(last, _)| *last > time) {
+            points.clear();
+        }
+        points.push_back((time, value));
+    }
+
+    // keeps one point before the window so that the line reaches its edge
+    fn trim(&self, end: f64) {
+        let mut points = self.points.borrow_mut();
+        if points.back().is_some_and(|(last, _)| *last > end) {
+            points.clear();
+        }
+        while points.get(1).is_some_and(|(time, _)| *time < end - WINDOW_SECONDS) {
+            points.pop_front();
+        }
+    }
+}
+
+/// Plots numeric fields of the messages on the bus over the last seconds of
+/// simulated time
+pub struct Plot {
+    topics: Mutable<Vec<TopicInfo>>,
+    topic: Mutable<Option<String>>,
+    field: Mutable<Option<String>>,
+    series: MutableVec<Rc<Series>>,
+    // the latest simulated time, changes whenever the chart needs to be redrawn
+    end: Mutable<f64>,
+}
+
+impl Plot {
+    pub fn new() -> Plot {
+        Plot {
+            topics: Default::default(),
+            topic: Default::default(),
+            field: Default::default(),
+            series: Default::default(),
+            end: Mutable::new(0.0),
+        }
+    }
+
+    async fn update(this: Rc<Plot>) {
+        let bus = crate::BUS.with(|bus| Rc::clone(bus));
+        let monitor = bus.create_monitor();
+        let mut interval = IntervalStream::new(UPDATE_MS);
+        let mut updates = 0;
+        loop {
+            if updates % REFRESH_UPDATES == 0 {
+                let topics = bus.topics();
+                if *this.topics.lock_ref() != topics {
+                    this.topics.set(topics);
+                }
+            }
+            updates += 1;
+            let series = this.series.lock_ref();
+            for (time, topic, message) in monitor.drain() {
+                for series in series.iter().filter(|series| series.topic == topic) {
+                    if let Some(value) = message.numeric_field(&series.field) {
+                        series.push(time, value);
+                    }
+                }
+            }
+            let end = bus.time();
+            for series in series.iter() {
+                series.trim(end);
+            }
+            drop(series);
+            this.end.set_neq(end);
+            if interval.next().await.is_none() {
+                break;
+            }
+        }
+    }
+
+    fn select_topic(&self, topic: Option<String>) {
+        let field = topic.as_ref()
+            .and_then(|topic| self.topics.lock_ref()
+                .iter()
+                .find(|info| info.name == *topic)
+                .and_then(|info| info.message_type.numeric_fields().first()))
+            .map(|field| field.to_string());
+        self.topic.set(topic);
+        self.field.set(field);
+    }
+
+    fn add_series(&self) {
+        let (Some(topic), Some(field)) = (self.topic.get_cloned(), self.field.get_cloned()) else {
+            return;
+        };
+        let mut series = self.series.lock_mut();
+        if series.iter().any(|series| series.topic == topic && series.field == field) {
+            return;
+        }
+        // the first color that is not in use yet
+        let color = chart::SERIES_COLORS.into_iter()
+            .find(|color| series.iter().all(|series| series.color != *color))
+            .unwrap_or(chart::SERIES_COLORS[series.len() % chart::SERIES_COLORS.len()]);
+        series.push_cloned(Rc::new(Series {
+            topic,
+            field,
+            color,
+            points: Default::default(),
+        }));
+    }
+
+    fn render_toolbar(this: &Rc<Plot>) -> Dom {
+        block!("p-2", "m-0", {
+            .class("has-background-white-ter")
+            .style("height", &format!("{TOOLBAR_HEIGHT}px"))
+            .style("white-space", "nowrap")
+            .style("overflow", "hidden")
+            .child(html!("span", {
+                .class("select")
+                .class("is-small")
+                .class("mr-2")
+                .child(html!("select" => HtmlSelectElement, {
+                    .children_signal_vec(map_ref! {
+                        let topics = this.topics.signal_cloned(),
+                        let selected = this.topic.signal_cloned() => {
+                            let none = html!("option", {
+                                .prop("value", "")
+                                .prop("selected", selected.is_none())
+                                .text("Select a topic")
+                            });
+                            [none].into_iter()
+                                .chain(topics.iter().map(|topic| html!("option", {
+                                    .prop("selected", selected.as_ref() == Some(&topic.name))
+                                    .prop("value", &topic.name)
+                                    .text(&topic.name)
+                                })))
+                                .collect()
+                        }
+                    }.to_signal_vec())
+                    .with_node!(select => {
+                        .event(clone!(this => move |_: events::Change| {
+                            let value = select.value();
+                            this.select_topic((!value.is_empty()).then_some(value));
+                        }))
+                    })
+                }))
+            }))
+            .child(html!("span", {
+                .class("select")
+                .class("is-small")
+                .class("mr-2")
+                .child(html!("select" => HtmlSelectElement, {
+                    .prop_signal("disabled", this.topic.signal_ref(|topic| topic.is_none()))
+                    .children_signal_vec(map_ref! {
+                        let topics = this.topics.signal_cloned(),
+                        let topic = this.topic.signal_cloned(),
+                        let selected = this.field.signal_cloned() => {
+                            topics.iter()
+                                .filter(|info| topic.as_ref() == Some(&info.name))
+                                .flat_map(|info| info.message_type.numeric_fields())
+                                .map(|field| html!("option", {
+                                    .prop("selected", selected.as_deref() == Some(*field))
+                                    .prop("value", *field)
+                                    .text(field)
+                                }))
+                                .collect()
+                        }
+                    }.to_signal_vec())
+                    .with_node!(select => {
+                        .event(clone!(this => move |_: events::Change| {
+                            let value = select.value();
+                            this.field.set((!value.is_empty()).then_some(value));
+                        }))
+                    })
+                }))
+            }))
+            .child(html!("button", {
+                .class("button")
+                .class("is-small")
+                .class("is-info")
+                .class("mr-2")
+                .text("Add")
+                .prop_signal("disabled", this.field.signal_ref(|field| field.is_none()))
+                .event(clone!(this => move |_: events::Click| {
+                    this.add_series();
+                }))
+            }))
+            .child(html!("span", {
+                .class("tags")
+                .style("display", "inline-flex")
+                .style("flex-wrap", "nowrap")
+                .style("margin-bottom", "0")
+                .children_signal_vec(this.series.signal_vec_cloned().map(clone!(this => move |series| html!("span", {
+                    .class("tag")
+                    .class("mb-0")
+                    .style("border-left", &format!("4px solid {}", series.color))
+                    .text(&series.label())
+                    .child(html!("button", {
+                        .class("delete")
+                        .class("is-small")
+                        .attr("title", "Remove from the plot")
+                        .event(clone!(this, series => move |_: events::Click| {
+                            this.series.lock_mut().retain(|other| !Rc::ptr_eq(other, &series));
+                        }))
+                    }))
+                }))))
+            }))
+        })
+    }
+
+    fn render_canvas(
+        this: &Rc<Plot>,
+        width: impl Signal<Item = u32> + 'static,
+        height: impl Signal<Item = u32> + 'static
+    ) -> Dom {
+        html!("canvas" => HtmlCanvasElement, {
+            .style("display", "block")
+            .with_node!(element => {
+                // redraw whenever the size changes, messages arrive or series come and go
+                .future(map_ref! {
+                    let width = width,
+                    let height = height,
+                    let _series = this.series.signal_vec_cloned().len(),
+                    let end = this.end.signal() => (*width, height.saturating_sub(TOOLBAR_HEIGHT), *end)
+                }.for_each(clone!(this => move |(width, height, end)| {
+                    let series = this.series.lock_ref();
+                    let points = series.iter()
+                        .map(|series| series.points.borrow())
+                        .collect::<Vec<_>>();
+                    let labels = series.iter()
+                        .map(|series| series.label())
+                        .collect::<Vec<_>>();
+                    let series = series.iter()
+                        .zip(points.iter().zip(labels.iter()))
+                        .map(|(series, (points, label))| chart::Series {
+                            label,
+                            color: series.color,
+                            points,
+                        })
+                        .collect::<Vec<_>>();
+                    canvas::resize(&element, width, height);
+                    chart::draw(&element, width, height, &series, end.max(WINDOW_SECONDS), WINDOW_SECONDS);
+                    async {}
+                })))
+            })
+        })
+    }
+
+    pub fn render(
+        this: &Rc<Plot>,
+        width: impl Signal<Item = u32> + 'static,
+        height: impl Signal<Item = u32> + 'static
+    ) -> impl Signal<Item = Option<Dom>> {
+        let dom = html!("div", {
+            .future(Self::update(this.clone()))
+            .child(Self::render_toolbar(this))
+            .child(Self::render_canvas(this, width, height))
+        });
+        signal::always(Some(dom))
+    }
+
+    pub fn label(&self) -> Dom {
+        html!("span", {
+            .text("Plot")
+        })
+    }
+
+    pub fn icon(&self) -> Dom {
+        svg!("svg", {
+            .attr("height", "1.25em")
+            .attr("viewBox", "0 0 24 24")
+            .child(svg!("path", {
+                .attr("d", ICON_PATH)
+            }))
+        })
+    }
+}
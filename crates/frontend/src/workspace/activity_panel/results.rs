@@ -0,0 +1,122 @@
+use std::rc::Rc;
+
+use dominator::{html, svg, Dom};
+use dominator_bulma::{block, tag};
+use futures_signals::signal::{self, Signal, SignalExt};
+
+use crate::simulation::scenario::{Report, Status};
+
+pub(crate) const ICON_PATH: &str = "M19,3H14.82C14.4,1.84 13.3,1 12,1C10.7,1 9.6,1.84 9.18,3H5A2,2 0 0,0 3,5V19A2,2 0 0,0 \
+    5,21H19A2,2 0 0,0 21,19V5A2,2 0 0,0 19,3M12,3A1,1 0 0,1 13,4A1,1 0 0,1 12,5A1,1 0 0,1 11,4A1,1 0 0,1 \
+    12,3M10,17L6,13L7.41,11.59L10,14.17L16.59,7.58L18,9L10,17Z";
+
+/// Shows the outcomes of the assertions in the scenario of the last simulation
+pub struct Results;
+
+impl Results {
+    pub fn new() -> Results {
+        Results
+    }
+
+    fn render_status(status: &Status) -> Dom {
+        tag!("is-light", {
+            .apply(|builder| match status {
+                Status::Pending => builder.text("pending").class("is-info"),
+                Status::Passed { .. } => builder.text("pass").class("is-success"),
+                Status::Failed { .. } => builder.text("fail").class("is-danger"),
+            })
+            .class("is-size-7")
+            .class("is-uppercase")
+            .style("min-width", "65px")
+            .style("letter-spacing", ".1em")
+        })
+    }
+
+    fn render_report(report: &Report) -> Dom {
+        html!("div", {
+            .class("p-3")
+            .child(html!("p", {
+                .class("mb-3")
+                .text(&match report.is_finished() {
+                    true => report.summary(),
+                    false => format!("{} after {:.2} of {} s", report.summary(), report.time, report.duration),
+                })
+            }))
+            .child(html!("table", {
+                .class("table")
+                .class("is-fullwidth")
+                .class("is-narrow")
+                .class("is-size-7")
+                .child(html!("thead", {
+                    .child(html!("tr", {
+                        .children(["Status", "Assertion", "Time", "Details"].map(|heading| html!("th", {
+                            .text(heading)
+                        })))
+                    }))
+                }))
+                .child(html!("tbody", {
+                    .children(report.outcomes.iter().map(|outcome| html!("tr", {
+                        .child(html!("td", {
+                            .child(Self::render_status(&outcome.status))
+                        }))
+                        .child(html!("td", {
+                            .text(&outcome.description)
+                        }))
+                        .child(html!("td", {
+                            .text(&match &outcome.status {
+                                Status::Pending => String::new(),
+                                Status::Passed { time } | Status::Failed { time, .. } => format!("{time:.2} s"),
+                            })
+                        }))
+                        .child(html!("td", {
+                            .text(match &outcome.status {
+                                Status::Failed { reason, .. } => reason.as_str(),
+                                _ => "",
+                            })
+                        }))
+                    })))
+                }))
+            }))
+        })
+    }
+
+    pub fn render(
+        _this: &Rc<Results>,
+        width: impl Signal<Item = u32> + 'static,
+        height: impl Signal<Item = u32> + 'static
+    ) -> impl Signal<Item = Option<Dom>> {
+        let report = crate::REPORT.with(|report| report.signal_cloned());
+        let dom = block!({
+            .class("has-background-white")
+            .style("overflow", "auto")
+            .style_signal("height", height.map(|height| format!("{height}px")))
+            .style_signal("width", width.map(|width| format!("{width}px")))
+            .child_signal(report.map(|report| Some(match report {
+                Some(report) => Self::render_report(&report),
+                None => html!("p", {
+                    .class("p-3")
+                    .class("is-size-7")
+                    .class("has-text-grey")
+                    .text("No scenario has been run, add a scenario.xml to the project and start the simulator to check it")
+                }),
+            })))
+        });
+        signal::always(Some(dom))
+    }
+
+    pub fn label(&self) -> Dom {
+        html!("span", {
+            .text("Results")
+        })
+    }
+
+    pub fn icon(&self) -> Dom {
+        svg!("svg", {
+            .attr("height", "1.25em")
+            .attr("viewBox", "0 0 24 24")
+            .child(svg!("path", {
+                .attr("d", ICON_PATH)
+            }))
+        })
+    }
+}
@@ -0,0 +1,255 @@
+use std::f64::consts::PI;
+
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+
+use crate::{launch::SensorType, simulation::{Pose, World}};
+
+// space in pixels between the world and the edge of the canvas for the scale
+const MARGIN: f64 = 36.0;
+// grid lines use the smallest spacing in meters that keeps them this many pixels apart
+const MIN_GRID_PIXELS: f64 = 40.0;
+// and draws no more than this many of them across the larger side of the world
+const MAX_GRID_LINES: f64 = 100.0;
+// the spacings are these steps in every power of ten from 0.1 m upwards
+const GRID_STEPS: [f64; 3] = [1.0, 2.0, 5.0];
+
+const BACKGROUND_COLOR: &str = "#f5f5f5";
+const GROUND_COLOR: &str = "#ffffff";
+const GRID_COLOR: &str = "#dbdbdb";
+const AXIS_COLOR: &str = "#b5b5b5";
+const BOUNDARY_COLOR: &str = "#4a4a4a";
+const TEXT_COLOR: &str = "#4a4a4a";
+const OBSTACLE_COLOR: &str = "#7a7a7a";
+const SENSOR_COLOR: &str = "rgba(241, 70, 104, 0.6)";
+const TELEOP_COLOR: &str = "#f14668";
+const ROBOT_COLORS: [&str; 6] = ["#485fc7", "#48c78e", "#f14668", "#ffb70f", "#3e8ed0", "#b86bff"];
+const FONT: &str = "11px sans-serif";
+
+/// Maps positions in the world to positions on the canvas. The world is viewed
+/// from above, so the x axis points right and the z axis points down.
+struct Transform {
+    scale: f64,
+    center: (f64, f64),
+}
+
+impl Transform {
+    fn new(size: [f64; 2], width: f64, height: f64) -> Transform {
+        let scale = ((width - 2.0 * MARGIN) / size[0])
+            .min((height - 2.0 * MARGIN) / size[1])
+            .max(f64::EPSILON);
+        Transform {
+            scale,
+            center: (width / 2.0, height / 2.0),
+        }
+    }
+
+    fn point(&self, x: f64, z: f64) -> (f64, f64) {
+        (self.center.0 + x * self.scale, self.center.1 + z * self.scale)
+    }
+
+    fn position(&self, x: f64, y: f64) -> (f64, f64) {
+        ((x - self.center.0) / self.scale, (y - self.center.1) / self.scale)
+    }
+}
+
+/// Returns the name of the robot at `x`, `y` in CSS pixels on a canvas of
+/// `width` by `height` CSS pixels
+pub fn robot_at(width: u32, height: u32, world: &World, x: f64, y: f64) -> Option<String> {
+    let transform = Transform::new(world.size, f64::from(width), f64::from(height));
+    let (x, z) = transform.position(x, y);
+    world.robots.iter()
+        .find(|robot| (robot.pose.x - x).hypot(robot.pose.z - z) <= robot.radius)
+        .map(|robot| robot.name.clone())
+}
+
+/// Resizes the canvas to `width` by `height` CSS pixels, using the device pixel
+/// ratio for the resolution so that the drawing stays sharp
+pub fn resize(canvas: &HtmlCanvasElement, width: u32, height: u32) {
+    let ratio = web_sys::window()
+        .map(|window| window.device_pixel_ratio())
+        .unwrap_or(1.0);
+    let (pixel_width, pixel_height) = ((f64::from(width) * ratio) as u32, (f64::from(height) * ratio) as u32);
+    // changing the size clears the canvas, so only do it when necessary
+    if canvas.width() != pixel_width || canvas.height() != pixel_height {
+        canvas.set_width(pixel_width);
+        canvas.set_height(pixel_height);
+        let style = canvas.style();
+        style.set_property("width", &format!("{width}px")).unwrap();
+        style.set_property("height", &format!("{height}px")).unwrap();
+    }
+}
+
+fn context(canvas: &HtmlCanvasElement) -> CanvasRenderingContext2d {
+    canvas.get_context("2d")
+        .unwrap()
+        .unwrap()
+        .unchecked_into()
+}
+
+/// Draws the world onto a canvas of `width` by `height` CSS pixels, or just the
+/// background if there is no world yet. The robot that is driven by the
+/// keyboard is highlighted, `teleop` holds its name and whether the canvas
+/// has the keyboard focus.
+pub fn draw(canvas: &HtmlCanvasElement, width: u32, height: u32, world: Option<&World>, teleop: Option<(&str, bool)>) {
+    let context = context(canvas);
+    let ratio = f64::from(canvas.width()) / f64::from(width.max(1));
+    let (width, height) = (f64::from(width), f64::from(height));
+    context.set_transform(ratio, 0.0, 0.0, ratio, 0.0, 0.0).unwrap();
+    context.set_fill_style(&JsValue::from_str(BACKGROUND_COLOR));
+    context.fill_rect(0.0, 0.0, width, height);
+
+    let Some(world) = world else {
+        return;
+    };
+    let transform = Transform::new(world.size, width, height);
+    draw_grid(&context, &transform, world.size);
+    for obstacle in world.obstacles.iter() {
+        let (x, y) = transform.point(obstacle.pose.x, obstacle.pose.z);
+        let (width, depth) = (obstacle.size[0] * transform.scale, obstacle.size[1] * transform.scale);
+        // positive headings turn counterclockwise, while the canvas rotates clockwise
+        context.save();
+        context.translate(x, y).unwrap();
+        context.rotate(-obstacle.pose.heading).unwrap();
+        context.set_fill_style(&JsValue::from_str(OBSTACLE_COLOR));
+        context.set_stroke_style(&JsValue::from_str(BOUNDARY_COLOR));
+        context.set_line_width(1.0);
+        context.fill_rect(-width / 2.0, -depth / 2.0, width, depth);
+        context.stroke_rect(-width / 2.0, -depth / 2.0, width, depth);
+        context.restore();
+    }
+    draw_sensors(&context, &transform, world);
+    for (index, robot) in world.robots.iter().enumerate() {
+        let (x, y) = transform.point(robot.pose.x, robot.pose.z);
+        let radius = robot.radius * transform.scale;
+        let (dx, dz) = robot.pose.direction();
+        // the pose estimated by odometry is outlined, it drifts away from the robot over time
+        let (odometry_x, odometry_y) = transform.point(robot.odometry.x, robot.odometry.z);
+        context.set_stroke_style(&JsValue::from_str(ROBOT_COLORS[index % ROBOT_COLORS.len()]));
+        context.set_line_width(1.0);
+        context.begin_path();
+        context.arc(odometry_x, odometry_y, radius, 0.0, 2.0 * PI).unwrap();
+        context.stroke();
+        context.set_fill_style(&JsValue::from_str(ROBOT_COLORS[index % ROBOT_COLORS.len()]));
+        context.set_stroke_style(&JsValue::from_str(BOUNDARY_COLOR));
+        context.set_line_width(1.0);
+        context.begin_path();
+        context.arc(x, y, radius, 0.0, 2.0 * PI).unwrap();
+        context.fill();
+        context.stroke();
+        // the heading indicator runs from the center to the front of the robot
+        context.set_line_width(2.0);
+        context.begin_path();
+        context.move_to(x, y);
+        context.line_to(x + dx * radius, y + dz * radius);
+        context.stroke();
+        context.set_fill_style(&JsValue::from_str(TEXT_COLOR));
+        context.set_font(FONT);
+        context.set_text_align("center");
+        context.set_text_baseline("bottom");
+        context.fill_text(&robot.name, x, y - radius - 2.0).unwrap();
+        if teleop.is_some_and(|(name, _)| name == robot.name) {
+            context.set_stroke_style(&JsValue::from_str(TELEOP_COLOR));
+            context.set_line_width(2.0);
+            context.set_line_dash(&js_sys::Array::of2(&4.0.into(), &3.0.into())).unwrap();
+            context.begin_path();
+            context.arc(x, y, radius + 5.0, 0.0, 2.0 * PI).unwrap();
+            context.stroke();
+            context.set_line_dash(&js_sys::Array::new()).unwrap();
+        }
+    }
+    if let Some((name, focused)) = teleop {
+        let hint = match focused {
+            true => format!("Driving {name} with the arrow keys or WASD"),
+            false => format!("Click here to drive {name} with the keyboard"),
+        };
+        context.set_fill_style(&JsValue::from_str(TELEOP_COLOR));
+        context.set_font(FONT);
+        context.set_text_align("left");
+        context.set_text_baseline("top");
+        context.fill_text(&hint, 4.0, 4.0).unwrap();
+    }
+}
+
+// range sensors are drawn as rays and lidars as the points that they hit
+fn draw_sensors(context: &CanvasRenderingContext2d, transform: &Transform, world: &World) {
+    context.set_stroke_style(&JsValue::from_str(SENSOR_COLOR));
+    context.set_fill_style(&JsValue::from_str(SENSOR_COLOR));
+    context.set_line_width(1.0);
+    for robot in world.robots.iter() {
+        let (x, y) = transform.point(robot.pose.x, robot.pose.z);
+        for sensor in robot.sensors.iter() {
+            for (angle, range) in sensor.angles().into_iter().zip(sensor.ranges.iter().copied()) {
+                let (dx, dz) = Pose { heading: robot.pose.heading + angle, ..robot.pose }.direction();
+                let (end_x, end_y) = (x + dx * range * transform.scale, y + dz * range * transform.scale);
+                match sensor.sensor_type {
+                    SensorType::Range { .. } => {
+                        context.begin_path();
+                        context.move_to(x, y);
+                        context.line_to(end_x, end_y);
+                        context.stroke();
+                    }
+                    SensorType::Lidar { .. } if range < sensor.range => context.fill_rect(end_x - 1.0, end_y - 1.0, 2.0, 2.0),
+                    SensorType::Lidar { .. } => {}
+                }
+            }
+        }
+    }
+}
+
+fn draw_grid(context: &CanvasRenderingContext2d, transform: &Transform, [width, depth]: [f64; 2]) {
+    let size = width.max(depth);
+    let spacing = (-1..)
+        .flat_map(|power| GRID_STEPS.map(|step| step * 10f64.powi(power)))
+        .find(|spacing| spacing * transform.scale >= MIN_GRID_PIXELS && size / spacing <= MAX_GRID_LINES)
+        .unwrap();
+    let decimals = match spacing < 1.0 {
+        true => 1,
+        false => 0,
+    };
+    let (left, top) = transform.point(-width / 2.0, -depth / 2.0);
+    let (right, bottom) = transform.point(width / 2.0, depth / 2.0);
+    context.set_fill_style(&JsValue::from_str(GROUND_COLOR));
+    context.fill_rect(left, top, right - left, bottom - top);
+
+    context.set_line_width(1.0);
+    context.set_font(FONT);
+    context.set_fill_style(&JsValue::from_str(TEXT_COLOR));
+    // lines of constant x with their labels below the world
+    context.set_text_align("center");
+    context.set_text_baseline("top");
+    for index in (-width / 2.0 / spacing).ceil() as i64..=(width / 2.0 / spacing).floor() as i64 {
+        let value = index as f64 * spacing;
+        let (x, _) = transform.point(value, 0.0);
+        context.set_stroke_style(&JsValue::from_str(if index == 0 { AXIS_COLOR } else { GRID_COLOR }));
+        context.begin_path();
+        context.move_to(x, top);
+        context.line_to(x, bottom);
+        context.stroke();
+        context.fill_text(&format!("{value:.decimals$}"), x, bottom + 4.0).unwrap();
+    }
+    // lines of constant z with their labels left of the world
+    context.set_text_align("right");
+    context.set_text_baseline("middle");
+    for index in (-depth / 2.0 / spacing).ceil() as i64..=(depth / 2.0 / spacing).floor() as i64 {
+        let value = index as f64 * spacing;
+        let (_, y) = transform.point(0.0, value);
+        context.set_stroke_style(&JsValue::from_str(if index == 0 { AXIS_COLOR } else { GRID_COLOR }));
+        context.begin_path();
+        context.move_to(left, y);
+        context.line_to(right, y);
+        context.stroke();
+        context.fill_text(&format!("{value:.decimals$}"), left - 4.0, y).unwrap();
+    }
+    // the units and the directions of the axes
+    context.set_text_align("right");
+    context.set_text_baseline("top");
+    context.fill_text("x [m] \u{2192}", right, bottom + 18.0).unwrap();
+    context.set_text_align("left");
+    context.set_text_baseline("bottom");
+    context.fill_text("z [m] \u{2193}", left, top - 4.0).unwrap();
+
+    context.set_stroke_style(&JsValue::from_str(BOUNDARY_COLOR));
+    context.set_line_width(2.0);
+    context.stroke_rect(left, top, right - left, bottom - top);
+}
@@ -0,0 +1,723 @@
+use std::{cell::RefCell, rc::{Rc, Weak}};
+
+use dominator::{clone, events, html, svg, Dom, EventOptions};
+use dominator_bulma::block;
+use futures::{future::{self, AbortHandle}, StreamExt};
+use futures_signals::{map_ref, signal::{self, Mutable, Signal, SignalExt}};
+use gloo_timers::future::IntervalStream;
+use web_sys::{HtmlCanvasElement, HtmlInputElement, HtmlSelectElement};
+
+use crate::{bag, bus, launch::ModelType, log, package, protocol, python, simulation::{self, clock::{self, Clock}, scenario::{self, Evaluation}}, vfs};
+
+pub(super) mod canvas;
+mod teleop;
+
+const LAUNCH_FILE_NAME: &str = "launch.xml";
+// the optional assertions that are checked while the simulation runs
+const SCENARIO_FILE_NAME: &str = "scenario.xml";
+// the interval between updates in wall time, each update takes as many steps
+// as needed to keep up with the selected speed
+const UPDATE_MS: u32 = 20;
+// the duration of a step in simulated time
+const STEP_SECONDS: f64 = 0.02;
+// all manual steps are taken at once on the UI thread, which also has to fit
+// a clock event per step and process into the mailboxes of the nodes
+const MAX_MANUAL_STEPS: u32 = 500;
+const TOOLBAR_HEIGHT: u32 = 48;
+const BAG_DIRECTORY_NAME: &str = "bags";
+
+// the paths of all bag files in the project
+fn find_bags(directory: &vfs::Directory, parent_path: &str, bags: &mut Vec<String>) {
+    let path = format!("{parent_path}/{}", directory.name.lock_ref());
+    for directory in directory.directories.lock_ref().iter() {
+        find_bags(directory, &path, bags);
+    }
+    bags.extend(directory.files.lock_ref()
+        .iter()
+        .map(|file| file.name.get_cloned())
+        .filter(|name| name.ends_with(&format!(".{}", bag::EXTENSION)))
+        .map(|name| format!("{path}/{name}")));
+}
+
+#[derive(Clone, PartialEq)]
+enum Status {
+    Stopped,
+    Running,
+    Failed(String),
+}
+
+// everything that is needed to run the simulation
+struct Prepared {
+    launch: crate::launch::Launch,
+    world: simulation::World,
+    processes: Vec<python::Process>,
+    player: Option<bag::Player>,
+    evaluation: Option<Evaluation>,
+    snapshot: protocol::Project,
+}
+
+pub struct Simulator {
+    status: Mutable<Status>,
+    launch: Mutable<Option<Rc<crate::launch::Launch>>>,
+    // the project as it was when the simulation started
+    snapshot: RefCell<Option<protocol::Project>>,
+    world: RefCell<Option<simulation::World>>,
+    processes: RefCell<Vec<python::Process>>,
+    // replays a bag instead of running the nodes
+    player: RefCell<Option<bag::Player>>,
+    recorder: RefCell<Option<bag::Recorder>>,
+    recording: Mutable<bool>,
+    // checks the assertions of the scenario until all of them are decided
+    evaluation: RefCell<Option<Evaluation>>,
+    // the path of the bag to replay or none to run the nodes
+    source: Mutable<Option<String>>,
+    bags: Mutable<Vec<String>>,
+    clock: RefCell<Clock>,
+    paused: Mutable<bool>,
+    speed: Mutable<f64>,
+    // the number of steps taken when stepping manually
+    step_count: Mutable<u32>,
+    // the robot that is driven by the keyboard
+    teleop: Mutable<Option<String>>,
+    teleop_publisher: RefCell<Option<bus::Publisher>>,
+    keys: RefCell<teleop::Keys>,
+    canvas_focused: Mutable<bool>,
+    // incremented after each update of the simulation
+    frame: Mutable<u64>,
+    task: RefCell<Option<AbortHandle>>,
+}
+
+impl Drop for Simulator {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl Simulator {
+    pub fn new() -> Simulator {
+        Simulator {
+            status: Mutable::new(Status::Stopped),
+            launch: Default::default(),
+            snapshot: Default::default(),
+            world: Default::default(),
+            processes: Default::default(),
+            player: Default::default(),
+            recorder: Default::default(),
+            recording: Mutable::new(false),
+            evaluation: Default::default(),
+            source: Default::default(),
+            bags: Default::default(),
+            clock: RefCell::new(Clock::new(STEP_SECONDS)),
+            paused: Mutable::new(false),
+            speed: Mutable::new(1.0),
+            step_count: Mutable::new(1),
+            teleop: Default::default(),
+            teleop_publisher: Default::default(),
+            keys: Default::default(),
+            canvas_focused: Mutable::new(false),
+            frame: Default::default(),
+            task: Default::default(),
+        }
+    }
+
+    // parses the launch file and creates the world along with either the
+    // processes for its nodes or a player for the bag at `source`
+    fn prepare(project: &Rc<vfs::Directory>, source: Option<&str>) -> Result<Prepared, String> {
+        let path = format!("/{}/{LAUNCH_FILE_NAME}", project.name.lock_ref());
+        let launch_file = project.lookup_file(&path)
+            .map_err(|error| error.to_string())?;
+        let launch = crate::launch::parse(&String::from_utf8_lossy(&launch_file.data.lock_ref()))
+            .map_err(|error| format!("{LAUNCH_FILE_NAME}:{error}"))?;
+        let bus = crate::BUS.with(|bus| Rc::clone(bus));
+        let world = simulation::World::new(&launch.world, &bus)
+            .map_err(|error| format!("{LAUNCH_FILE_NAME}: {error}"))?;
+        let scenario_path = format!("/{}/{SCENARIO_FILE_NAME}", project.name.lock_ref());
+        let evaluation = match project.lookup_file(&scenario_path) {
+            Ok(scenario_file) => {
+                let scenario = crate::launch::scenario::parse(&String::from_utf8_lossy(&scenario_file.data.lock_ref()))
+                    .map_err(|error| format!("{SCENARIO_FILE_NAME}:{error}"))?;
+                Some(Evaluation::new(scenario, &world)
+                    .map_err(|error| format!("{SCENARIO_FILE_NAME}: {error}"))?)
+            }
+            Err(_) => None,
+        };
+        let snapshot = protocol::Project::snapshot(project);
+        if let Some(source) = source {
+            let bag_file = project.lookup_file(source)
+                .map_err(|error| error.to_string())?;
+            let records = bag::read(&bag_file.data.lock_ref())
+                .map_err(|error| format!("{source}:{error}"))?;
+            let player = bag::Player::new(records, &world.topics(), &bus)
+                .map_err(|error| error.to_string())?;
+            return Ok(Prepared { launch, world, processes: Vec::new(), player: Some(player), evaluation, snapshot });
+        }
+        // every node is resolved before any of them is started
+        let packages = package::scan(project);
+        let programs = launch.nodes.iter()
+            .map(|node| package::resolve(&packages, &node.pkg, &node.exec))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| format!("{LAUNCH_FILE_NAME}: {error}"))?;
+        let processes = launch.nodes.iter()
+            .zip(programs)
+            .map(|(node, program)| python::Process::spawn(&format!("{}/{}", node.pkg, node.exec), &program, &snapshot, &node.parameters)
+                .map_err(|error| format!("could not start {program}: {error:?}")))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Prepared { launch, world, processes, player: None, evaluation, snapshot })
+    }
+
+    /// Takes a snapshot of the project and starts the launch file at its root
+    pub fn start(this: &Rc<Simulator>) {
+        let project = crate::PROJECT.with(|project| Rc::clone(project));
+        let source = this.source.get_cloned();
+        let Prepared { launch, world, processes, player, evaluation, snapshot } = match Self::prepare(&project, source.as_deref()) {
+            Ok(prepared) => prepared,
+            Err(error) => {
+                log::error(log::SIMULATOR, &error);
+                this.status.set(Status::Failed(error));
+                return;
+            }
+        };
+        match &source {
+            Some(source) => log::info(log::SIMULATOR, &format!("Started {LAUNCH_FILE_NAME} with {} models, replaying {source}",
+                launch.world.models.len())),
+            None => log::info(log::SIMULATOR, &format!("Started {LAUNCH_FILE_NAME} with {} nodes and {} models",
+                launch.nodes.len(), launch.world.models.len())),
+        }
+        if let Some(evaluation) = &evaluation {
+            let report = evaluation.report();
+            log::info(log::SIMULATOR, &format!("Checking {} assertions of {SCENARIO_FILE_NAME} for {} s",
+                report.outcomes.len(), report.duration));
+        }
+        crate::REPORT.with(|report| report.set(evaluation.as_ref().map(|evaluation| Rc::new(evaluation.report().clone()))));
+        *this.evaluation.borrow_mut() = evaluation;
+        *this.world.borrow_mut() = Some(world);
+        *this.processes.borrow_mut() = processes;
+        *this.player.borrow_mut() = player;
+        this.launch.set(Some(Rc::new(launch)));
+        *this.snapshot.borrow_mut() = Some(snapshot);
+        this.clock.borrow_mut().reset();
+        this.paused.set(false);
+        this.status.set(Status::Running);
+
+        // the task only holds a weak reference so that closing the tab ends the simulation
+        let (task, abort_handle) = future::abortable(Self::run(Rc::downgrade(this)));
+        wasm_bindgen_futures::spawn_local(async move {
+            let _ = task.await;
+        });
+        if let Some(previous_task) = this.task.replace(Some(abort_handle)) {
+            previous_task.abort();
+        }
+    }
+
+    async fn run(this: Weak<Simulator>) {
+        let mut interval = IntervalStream::new(UPDATE_MS);
+        let mut last_update = js_sys::Date::now();
+        while interval.next().await.is_some() {
+            let Some(this) = this.upgrade() else {
+                break;
+            };
+            let now = js_sys::Date::now();
+            let elapsed = (now - last_update) / 1000.0;
+            last_update = now;
+            if !this.paused.get() {
+                let steps = this.clock.borrow_mut().advance(elapsed, this.speed.get());
+                this.step(steps);
+            }
+        }
+    }
+
+    /// Advances the world by `count` steps, the nodes receive the simulated
+    /// time after each step so that their timers fire in simulated time
+    pub fn step(&self, count: u32) {
+        if count == 0 {
+            return;
+        }
+        if let Some(world) = self.world.borrow_mut().as_mut() {
+            let bus = crate::BUS.with(|bus| Rc::clone(bus));
+            for _ in 0..count {
+                bus.set_time(world.time);
+                let mut player = self.player.borrow_mut();
+                if let Some(bag) = player.as_mut() {
+                    if let Err(error) = bag.play(world.time) {
+                        log::error(log::SIMULATOR, &error.to_string());
+                    }
+                    if bag.is_finished() {
+                        log::info(log::SIMULATOR, &format!("Finished replaying at {:.2} s", world.time));
+                        player.take();
+                    }
+                }
+                drop(player);
+                if let Some(publisher) = self.teleop_publisher.borrow().as_ref() {
+                    let name = self.teleop.lock_ref();
+                    if let Some(robot) = world.robots.iter().find(|robot| Some(&robot.name) == name.as_ref()) {
+                        let velocity = self.keys.borrow().velocity(&robot.drive);
+                        publisher.publish(velocity).expect("teleop publishes velocities");
+                    }
+                }
+                // the messages that the world publishes during the step and the
+                // ones that the nodes publish in response carry the time after it
+                bus.set_time(world.time + STEP_SECONDS);
+                let contacts = world.step(STEP_SECONDS);
+                for contact in contacts.iter() {
+                    log::info(log::SIMULATOR, &format!("{} collided with {} at ({:.2}, {:.2})",
+                        contact.model, contact.other, contact.x, contact.z));
+                }
+                for process in self.processes.borrow().iter() {
+                    process.step(world.time);
+                }
+                if let Some(recorder) = self.recorder.borrow_mut().as_mut() {
+                    recorder.record(world.time);
+                }
+                if self.evaluate(world, &contacts) {
+                    break;
+                }
+            }
+        }
+        self.frame.replace_with(|frame| *frame + 1);
+    }
+
+    // checks the scenario after a step and pauses the simulation once all of
+    // its assertions are decided, returns whether that happened
+    fn evaluate(&self, world: &simulation::World, contacts: &[bus::message::Contact]) -> bool {
+        let mut evaluation = self.evaluation.borrow_mut();
+        let Some(current) = evaluation.as_mut() else {
+            return false;
+        };
+        if current.update(world, contacts) {
+            crate::REPORT.with(|report| report.set(Some(Rc::new(current.report().clone()))));
+        }
+        if !current.is_finished() {
+            return false;
+        }
+        let report = current.report();
+        for outcome in report.outcomes.iter() {
+            match &outcome.status {
+                scenario::Status::Passed { time } =>
+                    log::info(log::SIMULATOR, &format!("PASS {} at {time:.2} s", outcome.description)),
+                scenario::Status::Failed { time, reason } =>
+                    log::error(log::SIMULATOR, &format!("FAIL {} at {time:.2} s: {reason}", outcome.description)),
+                scenario::Status::Pending => {}
+            }
+        }
+        match report.failed() {
+            0 => log::info(log::SIMULATOR, &format!("Scenario passed: {}", report.summary())),
+            _ => log::error(log::SIMULATOR, &format!("Scenario failed: {}", report.summary())),
+        }
+        evaluation.take();
+        self.set_paused(true);
+        true
+    }
+
+    /// Drives the robot called `name` with the keyboard instead of its nodes
+    /// by publishing velocities on its own topic, or stops doing so
+    fn set_teleop(&self, name: Option<String>) {
+        self.keys.borrow_mut().release();
+        let publisher = name.as_ref().and_then(|name| {
+            let bus = crate::BUS.with(|bus| Rc::clone(bus));
+            bus.create_publisher(teleop::NODE, &format!("/{name}/velocity"), bus::MessageType::Velocity)
+                .map_err(|error| log::error(log::SIMULATOR, &format!("Could not drive {name}: {error}")))
+                .ok()
+        });
+        let name = name.filter(|_| publisher.is_some());
+        if let Some(name) = &name {
+            log::info(log::SIMULATOR, &format!("Driving {name} from the keyboard"));
+        }
+        *self.teleop_publisher.borrow_mut() = publisher;
+        self.teleop.set_neq(name);
+    }
+
+    fn set_paused(&self, paused: bool) {
+        self.clock.borrow_mut().reset();
+        self.paused.set_neq(paused);
+    }
+
+    /// Starts recording the messages on the bus or, if a recording is in
+    /// progress, saves it to a new bag in the project
+    fn toggle_recording(&self) {
+        match self.recorder.take() {
+            Some(recorder) => self.save_recording(recorder),
+            None => {
+                let bus = crate::BUS.with(|bus| Rc::clone(bus));
+                let skipped = self.world.borrow().as_ref().map(simulation::World::topics).unwrap_or_default();
+                *self.recorder.borrow_mut() = Some(bag::Recorder::new(&bus, skipped));
+                self.recording.set(true);
+            }
+        }
+    }
+
+    fn save_recording(&self, recorder: bag::Recorder) {
+        self.recording.set(false);
+        if recorder.is_empty() {
+            log::warn(log::SIMULATOR, "Nothing was recorded");
+            return;
+        }
+        let count = recorder.len();
+        let data = bag::write(&recorder.finish());
+        let project = crate::PROJECT.with(|project| Rc::clone(project));
+        let directory = format!("/{}/{BAG_DIRECTORY_NAME}", project.name.lock_ref());
+        let saved = project.lookup_directory(&directory)
+            .or_else(|_| project.create_directory(&directory, crate::DEFAULT_DIRECTORY_MODE))
+            .and_then(|bags| {
+                let name = (1..)
+                    .map(|index| format!("recording_{index}.{}", bag::EXTENSION))
+                    .find(|name| bags.child(name).is_none())
+                    .unwrap();
+                let path = format!("{directory}/{name}");
+                project.create_file(&path, crate::DEFAULT_FILE_MODE, data).map(|_| path)
+            });
+        match saved {
+            Ok(path) => log::info(log::SIMULATOR, &format!("Saved {count} messages to {path}")),
+            Err(error) => log::error(log::SIMULATOR, &format!("Could not save the recording: {error}")),
+        }
+    }
+
+    pub fn stop(&self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+            let time = self.world.borrow().as_ref().map_or(0.0, |world| world.time);
+            log::info(log::SIMULATOR, &format!("Stopped after {time:.2} s"));
+        }
+        if let Some(recorder) = self.recorder.take() {
+            self.save_recording(recorder);
+        }
+        if let Some(evaluation) = self.evaluation.take() {
+            log::warn(log::SIMULATOR, &format!("Stopped before the scenario finished: {}", evaluation.report().summary()));
+        }
+        self.set_teleop(None);
+        self.player.take();
+        // the nodes get to shut down by themselves before their workers are terminated
+        for process in self.processes.take() {
+            process.stop();
+        }
+        self.world.take();
+        self.snapshot.take();
+        self.status.set(Status::Stopped);
+    }
+
+    fn render_toolbar(this: &Rc<Simulator>) -> Dom {
+        let running = this.status.signal_ref(|status| *status == Status::Running).broadcast();
+        block!("p-2", "m-0", {
+            .class("has-background-white-ter")
+            .style("height", &format!("{TOOLBAR_HEIGHT}px"))
+            .style("white-space", "nowrap")
+            .style("overflow", "hidden")
+            .child(html!("button", {
+                .class("button")
+                .class("is-small")
+                .class("is-success")
+                .class("mr-2")
+                .text("Start")
+                .prop_signal("disabled", running.signal())
+                .event(clone!(this => move |_: events::Click| {
+                    Self::start(&this);
+                }))
+            }))
+            .child(html!("button", {
+                .class("button")
+                .class("is-small")
+                .class("is-danger")
+                .class("mr-2")
+                .text("Stop")
+                .prop_signal("disabled", signal::not(running.signal()))
+                .event(clone!(this => move |_: events::Click| {
+                    this.stop();
+                }))
+            }))
+            .child(Self::render_bag_controls(this, running.signal()))
+            .child(Self::render_clock_controls(this, running.signal()))
+            .child(Self::render_teleop_controls(this, running.signal()))
+            .child(Self::render_status(this))
+        })
+    }
+
+    fn render_bag_controls(this: &Rc<Simulator>, running: impl Signal<Item = bool> + 'static) -> Dom {
+        let running = running.broadcast();
+        html!("span", {
+            .class("mr-2")
+            .child(html!("span", {
+                .class("select")
+                .class("is-small")
+                .class("mr-2")
+                .child(html!("select" => HtmlSelectElement, {
+                    .attr("title", "Run the nodes or replay a recording instead")
+                    .prop_signal("disabled", running.signal())
+                    .children_signal_vec(this.bags.signal_cloned().map(clone!(this => move |bags| {
+                        let source = this.source.get_cloned();
+                        let nodes = html!("option", {
+                            .prop("value", "")
+                            .prop("selected", source.is_none())
+                            .text("Run nodes")
+                        });
+                        [nodes].into_iter()
+                            .chain(bags.into_iter().map(|bag| html!("option", {
+                                .prop("selected", source.as_deref() == Some(bag.as_str()))
+                                .prop("value", &bag)
+                                .text(&format!("Replay {bag}"))
+                            })))
+                            .collect()
+                    })).to_signal_vec())
+                    .with_node!(select => {
+                        // refresh the list whenever the project changes
+                        .future(clone!(this => async move {
+                            let update = || {
+                                let mut bags = Vec::new();
+                                crate::PROJECT.with(|project| find_bags(project, "", &mut bags));
+                                if this.source.lock_ref().as_ref().is_some_and(|source| !bags.contains(source)) {
+                                    this.source.set(None);
+                                }
+                                this.bags.set(bags);
+                            };
+                            update();
+                            let mut changes = vfs::subscribe();
+                            while changes.next().await.is_some() {
+                                update();
+                            }
+                        }))
+                        .event(clone!(this => move |_: events::Change| {
+                            let value = select.value();
+                            this.source.set((!value.is_empty()).then_some(value));
+                        }))
+                    })
+                }))
+            }))
+            .child(html!("button", {
+                .class("button")
+                .class("is-small")
+                .class_signal("is-danger", this.recording.signal())
+                .style("min-width", "6em")
+                .text_signal(this.recording.signal().map(|recording| match recording {
+                    true => "Save bag",
+                    false => "Record",
+                }))
+                .attr("title", "Record all messages on the bus into a bag")
+                .prop_signal("disabled", signal::not(running.signal()))
+                .event(clone!(this => move |_: events::Click| {
+                    this.toggle_recording();
+                }))
+            }))
+        })
+    }
+
+    fn render_clock_controls(this: &Rc<Simulator>, running: impl Signal<Item = bool> + 'static) -> Dom {
+        let running = running.broadcast();
+        let paused = map_ref! {
+            let running = running.signal(),
+            let paused = this.paused.signal() => *running && *paused
+        }.broadcast();
+        html!("span", {
+            .class("mr-2")
+            .child(html!("button", {
+                .class("button")
+                .class("is-small")
+                .class("mr-2")
+                .style("min-width", "5em")
+                .text_signal(this.paused.signal().map(|paused| match paused {
+                    true => "Resume",
+                    false => "Pause",
+                }))
+                .prop_signal("disabled", signal::not(running.signal()))
+                .event(clone!(this => move |_: events::Click| {
+                    this.set_paused(!this.paused.get());
+                }))
+            }))
+            .child(html!("button", {
+                .class("button")
+                .class("is-small")
+                .text("Step")
+                .attr("title", "Advance a paused simulation by the given number of steps")
+                .prop_signal("disabled", signal::not(paused.signal()))
+                .event(clone!(this => move |_: events::Click| {
+                    this.step(this.step_count.get());
+                }))
+            }))
+            .child(html!("input" => HtmlInputElement, {
+                .class("input")
+                .class("is-small")
+                .class("mr-2")
+                .style("width", "5em")
+                .attr("type", "number")
+                .attr("min", "1")
+                .attr("max", &MAX_MANUAL_STEPS.to_string())
+                .attr("title", &format!("Steps of {} ms", STEP_SECONDS * 1000.0))
+                .prop("value", "1")
+                .prop_signal("disabled", signal::not(paused.signal()))
+                .with_node!(input => {
+                    .event(clone!(this => move |_: events::Input| {
+                        this.step_count.set(input.value().parse().unwrap_or(1).clamp(1, MAX_MANUAL_STEPS));
+                    }))
+                })
+            }))
+            .child(html!("span", {
+                .class("buttons")
+                .class("has-addons")
+                .style("display", "inline-flex")
+                .style("margin-bottom", "0")
+                .children(clock::SPEEDS.map(|speed| html!("button", {
+                    .class("button")
+                    .class("is-small")
+                    .class("mb-0")
+                    .class_signal("is-info", this.speed.signal_ref(move |current| *current == speed))
+                    .text(&format!("{speed}\u{d7}"))
+                    .event(clone!(this => move |_: events::Click| {
+                        this.speed.set_neq(speed);
+                    }))
+                })))
+            }))
+        })
+    }
+
+    fn render_teleop_controls(this: &Rc<Simulator>, running: impl Signal<Item = bool> + 'static) -> Dom {
+        html!("span", {
+            .class("select")
+            .class("is-small")
+            .class("mr-2")
+            .child(html!("select" => HtmlSelectElement, {
+                .attr("title", "Drive a robot with the arrow keys or WASD")
+                .prop_signal("disabled", signal::not(running))
+                .children_signal_vec(map_ref! {
+                    let launch = this.launch.signal_cloned(),
+                    let teleop = this.teleop.signal_cloned() => {
+                        let robots = launch.iter()
+                            .flat_map(|launch| launch.world.models.iter())
+                            .filter(|model| model.model_type == ModelType::Turtlebot)
+                            .map(|model| html!("option", {
+                                .prop("selected", teleop.as_ref() == Some(&model.name))
+                                .prop("value", &model.name)
+                                .text(&format!("Drive {}", model.name))
+                            }));
+                        let off = html!("option", {
+                            .prop("value", "")
+                            .prop("selected", teleop.is_none())
+                            .text("Keyboard off")
+                        });
+                        [off].into_iter().chain(robots).collect()
+                    }
+                }.to_signal_vec())
+                .with_node!(select => {
+                    .event(clone!(this => move |_: events::Change| {
+                        let value = select.value();
+                        this.set_teleop((!value.is_empty()).then_some(value));
+                    }))
+                })
+            }))
+        })
+    }
+
+    fn render_status(this: &Rc<Simulator>) -> Dom {
+        html!("span", {
+            .class("is-size-7")
+            .child_signal(this.status.signal_cloned().map(clone!(this => move |status| Some(match status {
+                Status::Stopped => html!("span", {
+                    .text(&format!("Press start to run {LAUNCH_FILE_NAME}"))
+                }),
+                Status::Running => html!("span", {
+                    .text_signal(clone!(this => map_ref! {
+                        let launch = this.launch.signal_cloned(),
+                        let paused = this.paused.signal(),
+                        let _frame = this.frame.signal() => {
+                            let time = this.world.borrow().as_ref().map_or(0.0, |world| world.time);
+                            let (nodes, models) = launch.as_ref()
+                                .map_or((0, 0), |launch| (launch.nodes.len(), launch.world.models.len()));
+                            let paused = if *paused { " (paused)" } else { "" };
+                            format!("{nodes} nodes, {models} models, t = {time:.2} s{paused}")
+                        }
+                    }))
+                }),
+                Status::Failed(message) => html!("span", {
+                    .class("has-text-danger")
+                    .text(&message)
+                }),
+            }))))
+        })
+    }
+
+    fn render_canvas(
+        this: &Rc<Simulator>,
+        width: impl Signal<Item = u32> + 'static,
+        height: impl Signal<Item = u32> + 'static
+    ) -> Dom {
+        html!("canvas" => HtmlCanvasElement, {
+            .style("display", "block")
+            .style("outline", "none")
+            // makes the canvas focusable so that it receives keyboard events
+            .attr("tabindex", "0")
+            .with_node!(canvas => {
+                // redraw whenever the size changes, the simulation steps or it is stopped
+                .future(map_ref! {
+                    let width = width,
+                    let height = height,
+                    let _frame = this.frame.signal(),
+                    let _status = this.status.signal_cloned(),
+                    let _teleop = this.teleop.signal_cloned(),
+                    let _focused = this.canvas_focused.signal() => (*width, height.saturating_sub(TOOLBAR_HEIGHT))
+                }.for_each(clone!(this, canvas => move |(width, height)| {
+                    let teleop = this.teleop.lock_ref();
+                    let teleop = teleop.as_deref().map(|name| (name, this.canvas_focused.get()));
+                    canvas::resize(&canvas, width, height);
+                    canvas::draw(&canvas, width, height, this.world.borrow().as_ref(), teleop);
+                    async {}
+                })))
+                // clicking on a robot drives it with the keyboard
+                .event(clone!(this, canvas => move |event: events::PointerDown| {
+                    let rect = canvas.get_bounding_client_rect();
+                    let (x, y) = (f64::from(event.x()) - rect.left(), f64::from(event.y()) - rect.top());
+                    let robot = this.world.borrow().as_ref()
+                        .and_then(|world| canvas::robot_at(rect.width() as u32, rect.height() as u32, world, x, y));
+                    if robot.is_some() && *this.teleop.lock_ref() != robot {
+                        this.set_teleop(robot);
+                    }
+                }))
+            })
+            .event_with_options(&EventOptions::preventable(), clone!(this => move |event: events::KeyDown| {
+                if this.teleop.lock_ref().is_some() && this.keys.borrow_mut().set(&event.key(), true) {
+                    event.prevent_default();
+                }
+            }))
+            .event(clone!(this => move |event: events::KeyUp| {
+                this.keys.borrow_mut().set(&event.key(), false);
+            }))
+            .event(clone!(this => move |_: events::Focus| {
+                this.canvas_focused.set_neq(true);
+            }))
+            // the keys would otherwise stay pressed when the focus moves elsewhere
+            .event(clone!(this => move |_: events::Blur| {
+                this.keys.borrow_mut().release();
+                this.canvas_focused.set_neq(false);
+            }))
+        })
+    }
+
+    pub fn render(
+        this: &Rc<Simulator>,
+        width: impl Signal<Item = u32> + 'static,
+        height: impl Signal<Item = u32> + 'static
+    ) -> impl Signal<Item = Option<Dom>> {
+        let dom = html!("div", {
+            .child(Self::render_toolbar(this))
+            .child(Self::render_canvas(this, width, height))
+        });
+        signal::always(Some(dom))
+    }
+
+    pub fn label(&self) -> Dom {
+        html!("span", {
+            .text("Simulator")
+        })
+    }
+
+    pub fn icon(&self) -> Dom {
+        const PATH: &str = "M12,2A2,2 0 0,1 14,4C14,4.74 13.6,5.39 13,5.73V7H14A7,7 0 0,1 21,14H22A1,\
+            1 0 0,1 23,15V18A1,1 0 0,1 22,19H21V20A2,2 0 0,1 19,22H5A2,2 0 0,1 3,20V19H2A1,1 0 0,\
+            1 1,18V15A1,1 0 0,1 2,14H3A7,7 0 0,1 10,7H11V5.73C10.4,5.39 10,4.74 10,4A2,2 0 0,1 12,\
+            2M7.5,13A2.5,2.5 0 0,0 5,15.5A2.5,2.5 0 0,0 7.5,18A2.5,2.5 0 0,0 10,15.5A2.5,2.5 0 0,\
+            0 7.5,13M16.5,13A2.5,2.5 0 0,0 14,15.5A2.5,2.5 0 0,0 16.5,18A2.5,2.5 0 0,0 19,15.5A2.5,\
+            2.5 0 0,0 16.5,13Z";
+        svg!("svg", {
+            .attr("height", "1.25em")
+            .attr("viewBox", "0 0 24 24")
+            .child(svg!("path", {
+                .attr("d", PATH)
+            }))
+        })
+    }
+}
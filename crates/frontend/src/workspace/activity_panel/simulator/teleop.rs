@@ -0,0 +1,52 @@
+use crate::{bus::message::Velocity, simulation::DifferentialDrive};
+
+/// The node that velocity commands from the keyboard are published from
+pub const NODE: &str = "/teleop";
+// the speeds while a key is held down in m/s and rad/s
+const LINEAR_SPEED: f64 = 0.15;
+const ANGULAR_SPEED: f64 = 1.5;
+
+/// The keys that are held down, arrow keys and WASD drive the same way
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Keys {
+    forward: bool,
+    backward: bool,
+    left: bool,
+    right: bool,
+}
+
+impl Keys {
+    /// Updates the state of a key, see [KeyboardEvent.key], and returns
+    /// whether it is used for driving
+    ///
+    /// [KeyboardEvent.key]: https://developer.mozilla.org/en-US/docs/Web/API/KeyboardEvent/key
+    pub fn set(&mut self, key: &str, pressed: bool) -> bool {
+        let state = match key {
+            "ArrowUp" | "w" | "W" => &mut self.forward,
+            "ArrowDown" | "s" | "S" => &mut self.backward,
+            "ArrowLeft" | "a" | "A" => &mut self.left,
+            "ArrowRight" | "d" | "D" => &mut self.right,
+            _ => return false,
+        };
+        *state = pressed;
+        true
+    }
+
+    pub fn release(&mut self) {
+        *self = Keys::default();
+    }
+
+    /// The wheel velocities that drive the robot as requested by the keys,
+    /// opposite keys cancel each other out
+    pub fn velocity(&self, drive: &DifferentialDrive) -> Velocity {
+        let axis = |positive: bool, negative: bool| f64::from(u8::from(positive)) - f64::from(u8::from(negative));
+        let linear = LINEAR_SPEED * axis(self.forward, self.backward);
+        let angular = ANGULAR_SPEED * axis(self.left, self.right);
+        // invert [DifferentialDrive::velocity], the right wheel turns the other way
+        let offset = angular * drive.wheel_separation / 2.0;
+        Velocity {
+            left: (linear - offset) / drive.wheel_radius,
+            right: -(linear + offset) / drive.wheel_radius,
+        }
+    }
+}
@@ -97,6 +97,8 @@ fn render_category(category: &str) -> Dom {
             "INFO" => builder.text("info").class("is-success"),
             "WARN" => builder.text("warn").class("is-warning"),
             "ERROR" => builder.text("error").class("is-danger"),
+            "FATAL" => builder.text("fatal").class("is-danger"),
+            "DEBUG" => builder.text("debug").class("is-info"),
             _ => builder.text("unknown")
         })
         .class("is-size-7")
@@ -104,6 +104,10 @@ def build():
     ### Copy static assets
     print('Copying static assets')
     shutil.copytree(static_path, dist_path, dirs_exist_ok=True)
+    ### Copy the Python runtime for the node workers
+    print('Copying Pyodide')
+    shutil.copytree(build_path + '/node_modules/pyodide', dist_path + '/pyodide',
+        ignore=shutil.ignore_patterns('*.d.ts', '*.md', 'package.json'))
     return dist_path
 
 def watch():
@@ -0,0 +1,89 @@
+// Runs a Python program with Pyodide. The first message from the main thread
+// contains the mailbox and the start message of the protocol as JSON, which
+// holds the project files, the rclpy shim and the path of the program.
+// Afterwards, all events arrive through the mailbox, which is a ring buffer in
+// shared memory that starts with its read and write offsets. Changes to the
+// project during the run arrive there as well and are applied by the shim. The
+// messages are defined in src/protocol/mod.rs of the frontend crate.
+importScripts('pyodide/pyodide.js');
+
+// must match protocol::VERSION
+const PROTOCOL_VERSION = 2;
+const READ_INDEX = 0;
+const WRITE_INDEX = 1;
+const HEADER_BYTES = 8;
+const SHIM_PATH = '/lib/shim';
+
+const decoder = new TextDecoder();
+
+function createMailbox(buffer) {
+  const header = new Int32Array(buffer, 0, 2);
+  const data = new Uint8Array(buffer, HEADER_BYTES);
+
+  // copies `count` bytes starting at `offset`, wrapping around the end of the buffer
+  function read(offset, count) {
+    const bytes = new Uint8Array(count);
+    const split = Math.min(count, data.length - offset);
+    bytes.set(data.subarray(offset, offset + split));
+    bytes.set(data.subarray(0, count - split), split);
+    return bytes;
+  }
+
+  // returns the next message or null if none arrived within `timeout` milliseconds
+  return function receive(timeout) {
+    const offset = Atomics.load(header, READ_INDEX);
+    if (Atomics.load(header, WRITE_INDEX) === offset) {
+      Atomics.wait(header, WRITE_INDEX, offset, timeout ?? Infinity);
+      if (Atomics.load(header, WRITE_INDEX) === offset) {
+        return null;
+      }
+    }
+    const [a, b, c, d] = read(offset, 4);
+    const length = (a | (b << 8) | (c << 16) | (d << 24)) >>> 0;
+    const message = decoder.decode(read((offset + 4) % data.length, length));
+    Atomics.store(header, READ_INDEX, (offset + 4 + length) % data.length);
+    return message;
+  };
+}
+
+function decodeBase64(text) {
+  return Uint8Array.from(atob(text), (character) => character.charCodeAt(0));
+}
+
+function writeFile(pyodide, path, data) {
+  pyodide.FS.mkdirTree(path.substring(0, path.lastIndexOf('/')) || '/');
+  pyodide.FS.writeFile(path, data);
+}
+
+self.onmessage = async (event) => {
+  self.onmessage = null;
+  const { mailbox, start } = event.data;
+  // requests are sent as JSON, the shim encodes its own
+  const send = (request) => postMessage(request);
+  try {
+    const { version, program, shim, project } = JSON.parse(start);
+    if (version !== PROTOCOL_VERSION) {
+      throw new Error(`the worker speaks version ${PROTOCOL_VERSION} of the protocol, not ${version}`);
+    }
+    const pyodide = await loadPyodide({ indexURL: 'pyodide/' });
+    pyodide.registerJsModule('_bridge', { send, receive: createMailbox(mailbox) });
+    for (const { path, data } of shim) {
+      writeFile(pyodide, `${SHIM_PATH}/${path}`, decodeBase64(data));
+    }
+    for (const { path } of project.directories) {
+      pyodide.FS.mkdirTree(path);
+    }
+    for (const { path, data } of project.files) {
+      writeFile(pyodide, path, decodeBase64(data));
+    }
+    const directory = program.substring(0, program.lastIndexOf('/'));
+    pyodide.FS.chdir(directory);
+    const sys = pyodide.pyimport('sys');
+    sys.path.insert(0, SHIM_PATH);
+    sys.path.insert(0, directory);
+    pyodide.runPython(pyodide.FS.readFile(program, { encoding: 'utf8' }), { filename: program });
+    send(JSON.stringify({ type: 'exit' }));
+  } catch (error) {
+    send(JSON.stringify({ type: 'error', message: String(error.message ?? error) }));
+  }
+};